@@ -0,0 +1,95 @@
+//! Compares the chunk meshers on generated terrain.
+//!
+//! Run with `cargo bench --bench meshing`.
+
+use std::{
+    collections::HashMap,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use voxel::{
+    chunk::{border::ChunkBorder, light, registry::VoxelRegistry, Mesher},
+    generation::{
+        self, features::PendingWrites, structures::StructureLibrary, WorldGenerationOptions,
+    },
+};
+
+/// Number of times every chunk is meshed by each mesher.
+const ITERATIONS: u32 = 10;
+/// Horizontal radius, in chunks, of the generated terrain.
+const RADIUS: i32 = 2;
+
+fn main() {
+    let registry = VoxelRegistry::default();
+    let structures = StructureLibrary::load_directory("assets/structures", &registry)
+        .expect("to load structures");
+    let options = WorldGenerationOptions::default();
+
+    let max_chunk_y = options.max_height.div_ceil(options.chunk_size.y) as i32;
+    let mut chunks = HashMap::new();
+    let mut pending_writes = PendingWrites::default();
+    for x in -RADIUS..=RADIUS {
+        for y in 0..=max_chunk_y {
+            for z in -RADIUS..=RADIUS {
+                let (chunk, writes) = generation::generate_chunk(
+                    options,
+                    glam::ivec3(x, y, z),
+                    &registry,
+                    &structures,
+                );
+                chunks.insert(chunk.grid_position, chunk);
+                pending_writes.append(writes);
+            }
+        }
+    }
+    for chunk in chunks.values_mut() {
+        pending_writes.apply(chunk);
+        light::light_chunk(chunk, &registry);
+    }
+    let grid_positions = chunks.keys().copied().collect::<Vec<_>>();
+    for grid_position in grid_positions {
+        light::light_borders(&mut chunks, &registry, grid_position);
+    }
+
+    let jobs = chunks
+        .values()
+        .filter(|chunk| !chunk.is_empty())
+        .map(|chunk| (chunk, ChunkBorder::new(chunk, &chunks)))
+        .collect::<Vec<_>>();
+
+    println!(
+        "Meshing {} non-empty chunks of size {} ({} iterations)",
+        jobs.len(),
+        options.chunk_size,
+        ITERATIONS
+    );
+
+    for mesher in [Mesher::Greedy, Mesher::Binary] {
+        let mut elapsed = Duration::ZERO;
+        let mut vertices = 0;
+
+        for _ in 0..ITERATIONS {
+            for (chunk, border) in &jobs {
+                let start = Instant::now();
+                let (solid_mesh, transparent_mesh) =
+                    black_box(chunk.mesh_with(mesher, border, &registry));
+                elapsed += start.elapsed();
+
+                vertices += [solid_mesh, transparent_mesh]
+                    .iter()
+                    .flatten()
+                    .map(|mesh| mesh.vertices().len())
+                    .sum::<usize>();
+            }
+        }
+
+        let meshed_chunks = jobs.len() as u32 * ITERATIONS;
+        println!(
+            "{:?}: {:.3} ms per chunk, {} quads per iteration",
+            mesher,
+            (elapsed / meshed_chunks.max(1)).as_secs_f64() * 1000.0,
+            vertices / 4 / ITERATIONS as usize
+        );
+    }
+}
@@ -29,6 +29,9 @@ pub trait AppBehaviour {
 
     /// Renders the app.
     fn render(&mut self, frame: &mut glium::Frame);
+
+    /// Called once when the app is about to close, after the last frame.
+    fn exit(&mut self) {}
 }
 
 pub struct App {
@@ -62,7 +65,7 @@ impl App {
         }
     }
 
-    /// Run the given app.
+    /// Run the given app until it, or the window, asks to close.
     pub fn run(&mut self, mut app: impl AppBehaviour) {
         while !self.should_close {
             let current_time = Instant::now();
@@ -90,7 +93,7 @@ impl App {
                     }
                 });
             if self.should_close {
-                return;
+                break;
             }
 
             app.update(self.delta_time);
@@ -102,5 +105,7 @@ impl App {
 
             frame.finish().expect("to finish drawing frame");
         }
+
+        app.exit();
     }
 }
@@ -0,0 +1,206 @@
+//! Renders the terrain of a world generation preset to PNG images, without opening a window.
+//!
+//! Writes a greyscale heightmap and an image colored by surface voxel, see [`USAGE`].
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use voxel::{
+    chunk::registry::VoxelRegistry,
+    generation::{
+        presets,
+        preview::{Heightmap, HeightmapArea},
+    },
+};
+
+const USAGE: &str = "\
+Usage: voxel-cli <PRESET> [OPTIONS]
+
+Renders the terrain generated from the preset file at PRESET to PNG images.
+
+Options:
+  --seed <SEED>             Seed to use instead of the one in the preset
+  --center <X> <Z>          Center of the rendered area, in voxels [default: 0 0]
+  --size <WIDTH> <HEIGHT>   Size of the rendered area, in voxels [default: 2048 2048]
+  --resolution <W> <H>      Size of the images, in pixels [default: the size of the area]
+  --voxels <PATH>           Voxel definition file [default: the bundled definitions]
+  --output <PREFIX>         Prefix of the written images [default: heightmap]
+  --help                    Print this message
+
+Writes <PREFIX>-height.png, a greyscale heightmap, and <PREFIX>-color.png, colored by the voxel
+on the surface of the terrain.";
+
+/// Options parsed from the command line.
+struct Args {
+    preset: PathBuf,
+    seed: Option<u32>,
+    center: glam::DVec2,
+    size: glam::DVec2,
+    resolution: Option<glam::UVec2>,
+    voxels: Option<PathBuf>,
+    output: String,
+}
+
+impl Args {
+    /// Parses the command line arguments, returning `None` if the usage should be printed.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut preset = None;
+        let mut parsed = Self {
+            preset: PathBuf::new(),
+            seed: None,
+            center: glam::DVec2::ZERO,
+            size: glam::DVec2::splat(2048.0),
+            resolution: None,
+            voxels: None,
+            output: "heightmap".to_string(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{name}`"))
+            };
+
+            match arg.as_str() {
+                "--help" | "-h" => return Ok(None),
+                "--seed" => parsed.seed = Some(parse_value(&arg, &value(&arg)?)?),
+                "--center" => {
+                    parsed.center = glam::dvec2(
+                        parse_value(&arg, &value(&arg)?)?,
+                        parse_value(&arg, &value(&arg)?)?,
+                    );
+                }
+                "--size" => {
+                    parsed.size = glam::dvec2(
+                        parse_value(&arg, &value(&arg)?)?,
+                        parse_value(&arg, &value(&arg)?)?,
+                    );
+                }
+                "--resolution" => {
+                    parsed.resolution = Some(glam::uvec2(
+                        parse_value(&arg, &value(&arg)?)?,
+                        parse_value(&arg, &value(&arg)?)?,
+                    ));
+                }
+                "--voxels" => parsed.voxels = Some(PathBuf::from(value(&arg)?)),
+                "--output" => parsed.output = value(&arg)?,
+                _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+                _ if preset.is_none() => preset = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument `{arg}`")),
+            }
+        }
+
+        parsed.preset = preset.ok_or("missing preset file")?;
+        if parsed.size.cmple(glam::DVec2::ZERO).any() {
+            return Err("the size of the area must be positive".to_string());
+        }
+        if parsed
+            .resolution
+            .is_some_and(|resolution| resolution.min_element() == 0)
+        {
+            return Err("the resolution must be non-zero".to_string());
+        }
+
+        Ok(Some(parsed))
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{name}`"))
+}
+
+/// Writes an 8-bit PNG image.
+fn write_png(
+    path: &Path,
+    size: glam::UVec2,
+    color_type: png::ColorType,
+    data: &[u8],
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), size.x, size.y);
+    encoder.set_color(color_type);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(data))
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut options = presets::load_preset_file(&args.preset)
+        .map_err(|error| format!("{}: {error}", args.preset.display()))?;
+    if let Some(seed) = args.seed {
+        options.seed = seed;
+    }
+
+    let registry = match &args.voxels {
+        Some(path) => {
+            VoxelRegistry::load(path).map_err(|error| format!("{}: {error}", path.display()))?
+        }
+        None => VoxelRegistry::default(),
+    };
+
+    let area = HeightmapArea {
+        center: args.center,
+        extent: args.size,
+        resolution: args
+            .resolution
+            .unwrap_or_else(|| args.size.ceil().as_uvec2()),
+    };
+    let heightmap = Heightmap::sample(&options, area, &registry);
+
+    let height_path = PathBuf::from(format!("{}-height.png", args.output));
+    write_png(
+        &height_path,
+        area.resolution,
+        png::ColorType::Grayscale,
+        &heightmap.greyscale(&options),
+    )?;
+
+    let color_path = PathBuf::from(format!("{}-color.png", args.output));
+    write_png(
+        &color_path,
+        area.resolution,
+        png::ColorType::Rgba,
+        &heightmap.colors(&options, &registry),
+    )?;
+
+    println!(
+        "Wrote {} and {} (seed {}, sea level at {} voxels)",
+        height_path.display(),
+        color_path.display(),
+        options.seed,
+        options.sea_level_voxels()
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use glium::{DrawParameters, Surface};
+
+/// How far the outline is pushed out of the voxel, so that it is not hidden by the voxel's faces.
+const OUTLINE_OFFSET: f32 = 0.005;
+
+/// Outline drawn around the edges of a single voxel, like the voxel the camera is looking at.
+pub struct BlockOutline {
+    pub color: [f32; 4],
+
+    program: glium::Program,
+    vertex_buffer: glium::VertexBuffer<OutlineVertex>,
+    index_buffer: glium::IndexBuffer<u16>,
+}
+
+impl BlockOutline {
+    pub fn new(display: &glium::Display<glium::glutin::surface::WindowSurface>) -> Self {
+        // Corners of a unit cube, with the bits of the index selecting the X, Y and Z coordinates
+        let vertices = (0..8)
+            .map(|corner| OutlineVertex {
+                position: [
+                    (corner & 1) as f32,
+                    ((corner >> 1) & 1) as f32,
+                    ((corner >> 2) & 1) as f32,
+                ],
+            })
+            .collect::<Vec<_>>();
+        // Every pair of corners differing in a single coordinate is an edge
+        let indices = (0..8u16)
+            .flat_map(|corner| {
+                [1, 2, 4]
+                    .into_iter()
+                    .filter(move |bit| corner & bit == 0)
+                    .flat_map(move |bit| [corner, corner | bit])
+            })
+            .collect::<Vec<_>>();
+
+        let vertex_buffer =
+            glium::VertexBuffer::new(display, &vertices).expect("to create outline vertex buffer");
+        let index_buffer =
+            glium::IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)
+                .expect("to create outline index buffer");
+
+        let program = glium::Program::from_source(
+            display,
+            include_str!("shaders/outline.vert"),
+            include_str!("shaders/outline.frag"),
+            None,
+        )
+        .expect("to compile outline shaders");
+
+        Self {
+            color: [0.05, 0.05, 0.05, 0.8],
+
+            program,
+            vertex_buffer,
+            index_buffer,
+        }
+    }
+
+    /// Draws the outline around the voxel at the given position.
+    pub fn draw(
+        &self,
+        frame: &mut glium::Frame,
+        view_projection: glam::Mat4,
+        position: glam::IVec3,
+    ) {
+        let model = glam::Mat4::from_scale_rotation_translation(
+            glam::Vec3::splat(1.0 + 2.0 * OUTLINE_OFFSET),
+            glam::Quat::IDENTITY,
+            position.as_vec3() - OUTLINE_OFFSET,
+        );
+
+        frame
+            .draw(
+                &self.vertex_buffer,
+                &self.index_buffer,
+                &self.program,
+                &glium::uniform! {
+                    mvp: (view_projection * model).to_cols_array_2d(),
+                    outline_color: self.color,
+                },
+                &DrawParameters {
+                    depth: glium::Depth {
+                        test: glium::draw_parameters::DepthTest::IfLessOrEqual,
+                        write: false,
+                        ..Default::default()
+                    },
+                    blend: glium::Blend::alpha_blending(),
+                    line_width: Some(2.0),
+                    ..Default::default()
+                },
+            )
+            .expect("to draw block outline");
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct OutlineVertex {
+    pub position: [f32; 3],
+}
+implement_vertex!(OutlineVertex, position);
@@ -15,13 +15,15 @@ impl Camera {
         }
     }
 
-    pub fn view_matrix(&self) -> glam::Mat4 {
+    /// Returns the direction the camera is looking in.
+    pub fn front(&self) -> glam::Vec3 {
         let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
         let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
-        let front =
-            glam::Vec3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize();
+        glam::Vec3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize()
+    }
 
-        glam::Mat4::look_at_rh(self.position, self.position + front, glam::Vec3::Y)
+    pub fn view_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::look_at_rh(self.position, self.position + self.front(), glam::Vec3::Y)
     }
 }
 
@@ -125,12 +127,7 @@ impl CameraController {
     }
 
     pub fn update_camera(&mut self, camera: &mut Camera, delta_time: f32) {
-        let front = glam::Vec3::new(
-            camera.yaw.cos() * camera.pitch.cos(),
-            camera.pitch.sin(),
-            camera.yaw.sin() * camera.pitch.cos(),
-        )
-        .normalize();
+        let front = camera.front();
         let right = front.cross(glam::Vec3::Y).normalize();
 
         let move_speed = self.current_speed * delta_time;
@@ -1,56 +1,51 @@
-use std::collections::{HashMap, HashSet};
-
-use mesh::{Axis, Direction, Mesh};
-
+use border::ChunkBorder;
+use light::{Light, LightStorage};
+use mesh::{Axis, Direction, FaceShading, Mesh};
+use registry::VoxelRegistry;
+use storage::VoxelStorage;
+
+mod binary_mesh;
+pub mod border;
+pub mod light;
 pub mod mesh;
+pub mod registry;
+pub mod storage;
 
 use crate::{transform::Transform, utils::coord_to_index};
 
 pub struct VoxelUniforms {
     pub view_projection: [[f32; 4]; 4],
+    /// Color of the sunlight.
     pub light_color: [f32; 3],
-    pub light_position: [f32; 3],
+    /// Direction the sunlight comes from.
+    pub light_direction: [f32; 3],
 }
 
 pub type VoxelColor = [f32; 4];
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Voxel {
-    Air,
-    Stone,
-    Grass,
-    Dirt,
-    Water,
-    Sand,
-    Snow,
-}
-
-impl Into<VoxelColor> for Voxel {
-    fn into(self) -> VoxelColor {
-        match self {
-            Voxel::Air => [0.0, 0.0, 0.0, 0.0],
-            Voxel::Stone => [0.69, 0.72, 0.72, 1.0],
-            Voxel::Grass => [0.23, 0.82, 0.24, 1.0],
-            Voxel::Dirt => [0.63, 0.45, 0.29, 1.0],
-            Voxel::Water => [0.0, 0.62, 1.0, 0.8],
-            Voxel::Sand => [0.93, 0.89, 0.55, 1.0],
-            Voxel::Snow => [0.94, 0.98, 0.98, 1.0],
-        }
-    }
-}
+/// Compact numeric identifier of a voxel type registered in a [`VoxelRegistry`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Voxel(u16);
 
 impl Voxel {
-    pub fn is_air(self) -> bool {
-        self == Voxel::Air
-    }
+    /// Air is always registered with the identifier `0`.
+    pub const AIR: Voxel = Voxel(0);
 
-    pub fn is_liquid(self) -> bool {
-        self == Voxel::Water
+    pub fn is_air(self) -> bool {
+        self == Voxel::AIR
     }
+}
 
-    pub fn is_solid(self) -> bool {
-        !self.is_air() && !self.is_liquid()
-    }
+/// Algorithm used to create the mesh of a chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mesher {
+    /// Greedy meshing over a 2D mask of visible faces for every slice of the chunk.
+    #[default]
+    Greedy,
+    /// Greedy meshing over bitmasks of voxel columns.
+    ///
+    /// Chunks with a dimension larger than 64 voxels fall back to [`Mesher::Greedy`].
+    Binary,
 }
 
 /// Represents a chunk of the world.
@@ -63,13 +58,21 @@ pub struct Chunk {
     /// The transform of the chunk.
     transform: Transform,
     /// The voxels of the chunk.
-    voxels: Vec<Voxel>,
+    voxels: VoxelStorage,
+    /// The light of the voxels, see [`light::light_chunk`].
+    light: LightStorage,
+    /// Whether the chunk was modified since it was generated or loaded.
+    dirty: bool,
+    /// Neighbouring chunks whose features were written into the chunk, one bit per neighbour, see
+    /// [`PendingWrites`](crate::generation::features::PendingWrites).
+    received_features: u32,
 }
 
 impl Chunk {
     /// Creates a new empty chunk with the given grid position and size.
     pub fn new(grid_position: glam::IVec3, size: glam::UVec3) -> Self {
         let transform_position = grid_position * size.as_ivec3();
+        let len = size.x as usize * size.y as usize * size.z as usize;
 
         Self {
             grid_position,
@@ -79,10 +82,18 @@ impl Chunk {
                 rotation: glam::Quat::IDENTITY,
                 scale: glam::Vec3::ONE,
             },
-            voxels: vec![Voxel::Air; size.x as usize * size.y as usize * size.z as usize],
+            voxels: VoxelStorage::new(Voxel::AIR, len),
+            light: LightStorage::new(Light::default(), len),
+            dirty: false,
+            received_features: 0,
         }
     }
 
+    /// Returns the size of the chunk.
+    pub fn size(&self) -> glam::UVec3 {
+        self.size
+    }
+
     /// Returns the transformation of the chunk.
     pub fn transform(&self) -> Transform {
         self.transform
@@ -100,15 +111,62 @@ impl Chunk {
 
     /// Sets the voxel at the given position.
     pub fn set_voxel(&mut self, position: glam::UVec3, voxel: Voxel) {
+        if position.x >= self.size.x || position.y >= self.size.y || position.z >= self.size.z {
+            return;
+        }
+
         let index = coord_to_index(position, self.size);
-        if self.voxels.get(index).is_some() {
-            self.voxels[index] = voxel;
+        self.voxels.set(index, voxel);
+    }
+
+    /// Returns the light at the given position.
+    pub fn light(&self, position: glam::UVec3) -> Option<Light> {
+        if position.x >= self.size.x || position.y >= self.size.y || position.z >= self.size.z {
+            return None;
+        }
+
+        self.light.get(coord_to_index(position, self.size))
+    }
+
+    /// Sets the light at the given position.
+    pub fn set_light(&mut self, position: glam::UVec3, light: Light) {
+        if position.x >= self.size.x || position.y >= self.size.y || position.z >= self.size.z {
+            return;
         }
+
+        let index = coord_to_index(position, self.size);
+        self.light.set(index, light);
     }
 
     /// Returns whether the chunk entirely consists of air voxels.
     pub fn is_empty(&self) -> bool {
-        self.voxels.iter().all(|voxel| voxel.is_air())
+        self.voxels.uniform().is_some_and(|voxel| voxel.is_air())
+    }
+
+    /// Returns whether the chunk was modified since it was generated or loaded, and needs to be
+    /// saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Sets whether the chunk needs to be saved.
+    pub fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    /// Returns the neighbouring chunks whose features were written into the chunk, as a bit mask.
+    pub fn received_features(&self) -> u32 {
+        self.received_features
+    }
+
+    /// Sets the neighbouring chunks whose features were written into the chunk, as a bit mask.
+    pub fn set_received_features(&mut self, received_features: u32) {
+        self.received_features = received_features;
+    }
+
+    /// Returns an estimate of the memory used by the chunk, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() + self.voxels.heap_size() + self.light.heap_size()
     }
 }
 
@@ -118,14 +176,44 @@ impl Chunk {
     /// Returns a tuple of two optional meshes. The first mesh is the solid mesh and the second mesh is the transparent mesh.
     pub fn mesh(
         &self,
-        chunk_neighbours: &HashMap<glam::IVec3, Chunk>,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
     ) -> (Option<Mesh>, Option<Mesh>) {
+        self.mesh_with(Mesher::Greedy, chunk_border, registry)
+    }
+
+    /// Creates a new mesh for the chunk using the given meshing algorithm.
+    ///
+    /// Returns a tuple of two optional meshes. The first mesh is the solid mesh and the second mesh is the transparent mesh.
+    pub fn mesh_with(
+        &self,
+        mesher: Mesher,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
+    ) -> (Option<Mesh>, Option<Mesh>) {
+        let mesher = if self.size.max_element() > binary_mesh::MAX_SIZE {
+            Mesher::Greedy
+        } else {
+            mesher
+        };
+
+        let mesh_voxels = |voxel_to_mesh: &dyn Fn(Voxel) -> bool,
+                           neighbour_condition: &dyn Fn(Voxel) -> bool| {
+            match mesher {
+                Mesher::Greedy => {
+                    self.greedy_mesh(chunk_border, registry, voxel_to_mesh, neighbour_condition)
+                }
+                Mesher::Binary => {
+                    self.binary_mesh(chunk_border, registry, voxel_to_mesh, neighbour_condition)
+                }
+            }
+        };
+
         let mesh = {
-            let mesh = self.greedy_mesh(
-                chunk_neighbours,
-                |voxel| voxel.is_solid(),
-                |voxel| !voxel.is_solid(),
-            );
+            let mut mesh = mesh_voxels(&|voxel| registry.is_solid(voxel), &|voxel| {
+                !registry.is_solid(voxel)
+            });
+            self.mesh_sprites(&mut mesh, registry);
             if mesh.is_empty() {
                 None
             } else {
@@ -134,11 +222,7 @@ impl Chunk {
         };
 
         let transparent_mesh = {
-            let mesh = self.greedy_mesh(
-                chunk_neighbours,
-                |voxel| voxel.is_liquid(),
-                |voxel| voxel.is_air(),
-            );
+            let mesh = mesh_voxels(&|voxel| registry.is_liquid(voxel), &|voxel| voxel.is_air());
             if mesh.is_empty() {
                 None
             } else {
@@ -149,9 +233,16 @@ impl Chunk {
         (mesh, transparent_mesh)
     }
 
+    /// Meshes the voxels accepted by `voxel_to_mesh` using greedy meshing.
+    ///
+    /// Each slice of the chunk along every axis and direction is reduced to a 2D mask of visible
+    /// faces, where a face is visible if the voxel in front of it satisfies `neighbour_condition`.
+    /// Adjacent faces in the mask are only merged when they belong to the same voxel type, and have
+    /// the same ambient occlusion and light.
     fn greedy_mesh<V, N>(
         &self,
-        chunk_neighbours: &HashMap<glam::IVec3, Chunk>,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
         voxel_to_mesh: V,
         neighbour_condition: N,
     ) -> Mesh
@@ -162,98 +253,77 @@ impl Chunk {
         let mut mesh = Mesh::new();
 
         for axis in [Axis::X, Axis::Y, Axis::Z] {
-            for direction in [Direction::Positive, Direction::Negative] {
-                let mut visited = HashSet::new();
+            let depth = axis.get_component(self.size);
+            let plane_dimensions = axis.get_plane(self.size);
+            let mut mask = vec![None; plane_dimensions.x as usize * plane_dimensions.y as usize];
 
-                let plane_dimensions = match axis {
-                    Axis::X => glam::uvec2(self.size.y, self.size.z),
-                    Axis::Y => glam::uvec2(self.size.x, self.size.z),
-                    Axis::Z => glam::uvec2(self.size.x, self.size.y),
-                };
+            for direction in [Direction::Positive, Direction::Negative] {
+                let normal = axis.get_normal(direction).as_ivec3();
+
+                for layer in 0..depth {
+                    for v in 0..plane_dimensions.y {
+                        for u in 0..plane_dimensions.x {
+                            let position = axis.plane_to_position(layer, glam::uvec2(u, v));
+                            let index = (u + v * plane_dimensions.x) as usize;
+
+                            mask[index] = self
+                                .get_voxel(position)
+                                .copied()
+                                .filter(|voxel| voxel_to_mesh(*voxel))
+                                .filter(|_| {
+                                    self.get_voxel_or_neighbour(
+                                        chunk_border,
+                                        position.as_ivec3() + normal,
+                                    )
+                                    .is_some_and(&neighbour_condition)
+                                })
+                                .map(|voxel| {
+                                    (
+                                        voxel,
+                                        self.face_shading(
+                                            chunk_border,
+                                            registry,
+                                            position,
+                                            axis,
+                                            direction,
+                                        ),
+                                    )
+                                });
+                        }
+                    }
 
-                for x in 0..self.size.x {
-                    for y in 0..self.size.y {
-                        for z in 0..self.size.z {
-                            let position = glam::uvec3(x, y, z);
-                            let voxel = self.get_voxel(position);
-                            if visited.contains(&position) || voxel.is_none() {
+                    for v in 0..plane_dimensions.y {
+                        let mut u = 0;
+                        while u < plane_dimensions.x {
+                            let index = (u + v * plane_dimensions.x) as usize;
+                            let Some(face) = mask[index] else {
+                                u += 1;
                                 continue;
-                            } else if let Some(voxel) = voxel {
-                                if !voxel_to_mesh(*voxel) {
-                                    continue;
-                                }
-                            }
-
-                            let neighbour_position =
-                                (position.as_vec3() + axis.get_normal(direction)).as_ivec3();
-                            if neighbour_position.x < 0
-                                || neighbour_position.x >= self.size.x as i32
-                                || neighbour_position.y < 0
-                                || neighbour_position.y >= self.size.y as i32
-                                || neighbour_position.z < 0
-                                || neighbour_position.z >= self.size.z as i32
-                            {
-                                if !self.voxel_has_neigbour(
-                                    chunk_neighbours,
-                                    position,
-                                    axis,
-                                    direction,
-                                    &neighbour_condition,
-                                ) {
-                                    continue;
-                                }
-                            } else {
-                                let neighbour_voxel = self.get_voxel(neighbour_position.as_uvec3());
-                                if let Some(neighbour_voxel) = neighbour_voxel {
-                                    if !neighbour_condition(*neighbour_voxel) {
-                                        continue;
-                                    }
-                                }
-                            }
-
-                            let plane = match axis {
-                                Axis::X => glam::uvec2(y, z),
-                                Axis::Y => glam::uvec2(x, z),
-                                Axis::Z => glam::uvec2(x, y),
                             };
-                            let mut size = glam::uvec2(1, 1);
-                            while plane.x + size.x < plane_dimensions.x {
-                                let next_position = match axis {
-                                    Axis::X => glam::uvec3(x, y + size.x, z),
-                                    Axis::Y => glam::uvec3(x + size.x, y, z),
-                                    Axis::Z => glam::uvec3(x + size.x, y, z),
-                                };
-
-                                let next_voxel = self.get_voxel(next_position);
-                                if visited.contains(&next_position)
-                                    || next_voxel.is_none()
-                                    || next_voxel != voxel
-                                {
-                                    break;
-                                }
 
+                            let mut size = glam::uvec2(1, 1);
+                            while u + size.x < plane_dimensions.x
+                                && mask[index + size.x as usize] == Some(face)
+                            {
                                 size.x += 1;
                             }
 
-                            'outer: while plane.y + size.y < plane_dimensions.y {
-                                for w in 0..size.x {
-                                    let next_position = match axis {
-                                        Axis::X => glam::uvec3(x, y + w, z + size.y),
-                                        Axis::Y => glam::uvec3(x + w, y, z + size.y),
-                                        Axis::Z => glam::uvec3(x + w, y + size.y, z),
-                                    };
-
-                                    let next_voxel = self.get_voxel(next_position);
-                                    if visited.contains(&next_position)
-                                        || next_voxel.is_none()
-                                        || next_voxel != voxel
-                                    {
+                            'outer: while v + size.y < plane_dimensions.y {
+                                let row = index + (size.y * plane_dimensions.x) as usize;
+                                for w in 0..size.x as usize {
+                                    if mask[row + w] != Some(face) {
                                         break 'outer;
                                     }
                                 }
                                 size.y += 1;
                             }
 
+                            for h in 0..size.y {
+                                let row = index + (h * plane_dimensions.x) as usize;
+                                mask[row..row + size.x as usize].fill(None);
+                            }
+
+                            let position = axis.plane_to_position(layer, glam::uvec2(u, v));
                             mesh.add_face(
                                 position.as_vec3()
                                     + match direction {
@@ -263,19 +333,11 @@ impl Chunk {
                                 size.as_vec2(),
                                 axis,
                                 direction,
-                                Into::<VoxelColor>::into(*voxel.unwrap()),
+                                registry.color(face.0),
+                                face.1,
                             );
 
-                            for w in 0..size.x {
-                                for h in 0..size.y {
-                                    let visited_position = match axis {
-                                        Axis::X => glam::uvec3(x, y + w, z + h),
-                                        Axis::Y => glam::uvec3(x + w, y, z + h),
-                                        Axis::Z => glam::uvec3(x + w, y + h, z),
-                                    };
-                                    visited.insert(visited_position);
-                                }
-                            }
+                            u += size.x;
                         }
                     }
                 }
@@ -285,45 +347,444 @@ impl Chunk {
         mesh
     }
 
-    fn voxel_has_neigbour(
+    /// Adds a cross of quads for every voxel that is neither solid nor a liquid, such as plants.
+    fn mesh_sprites(&self, mesh: &mut Mesh, registry: &VoxelRegistry) {
+        for z in 0..self.size.z {
+            for y in 0..self.size.y {
+                for x in 0..self.size.x {
+                    let position = glam::uvec3(x, y, z);
+                    let Some(voxel) = self.get_voxel(position).copied() else {
+                        continue;
+                    };
+                    if voxel.is_air() || registry.is_solid(voxel) || registry.is_liquid(voxel) {
+                        continue;
+                    }
+
+                    let light = self.light(position).unwrap_or_default();
+                    mesh.add_cross(
+                        position.as_vec3(),
+                        registry.color(voxel),
+                        [light.sky(), light.block()],
+                    );
+                }
+            }
+        }
+    }
+
+    /// Computes the ambient occlusion and the light of the corners of a voxel face.
+    ///
+    /// Each corner is occluded by the solid voxels in front of the face that share an edge or a
+    /// corner with it, including voxels in neighbouring chunks. The light of a corner is the average
+    /// light of the voxel in front of the face and of those surrounding voxels that are not solid,
+    /// which smooths the light across faces.
+    fn face_shading(
         &self,
-        chunk_neighbours: &HashMap<glam::IVec3, Chunk>,
-        voxel_position: glam::UVec3,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
+        position: glam::UVec3,
         axis: Axis,
         direction: Direction,
-        condition: impl Fn(Voxel) -> bool,
-    ) -> bool {
-        let neighbour_chunk_position = self.grid_position + axis.get_normal(direction).as_ivec3();
-
-        if let Some(neighbour_chunk) = chunk_neighbours.get(&neighbour_chunk_position) {
-            let neighbour_position = match (axis, direction) {
-                (Axis::X, Direction::Positive) => {
-                    glam::uvec3(0, voxel_position.y, voxel_position.z)
-                }
-                (Axis::X, Direction::Negative) => {
-                    glam::uvec3(self.size.x - 1, voxel_position.y, voxel_position.z)
-                }
-                (Axis::Y, Direction::Positive) => {
-                    glam::uvec3(voxel_position.x, 0, voxel_position.z)
-                }
-                (Axis::Y, Direction::Negative) => {
-                    glam::uvec3(voxel_position.x, self.size.y - 1, voxel_position.z)
-                }
-                (Axis::Z, Direction::Positive) => {
-                    glam::uvec3(voxel_position.x, voxel_position.y, 0)
-                }
-                (Axis::Z, Direction::Negative) => {
-                    glam::uvec3(voxel_position.x, voxel_position.y, self.size.z - 1)
+    ) -> FaceShading {
+        let front = position.as_ivec3() + axis.get_normal(direction).as_ivec3();
+        let u = axis.plane_to_position(0, glam::UVec2::X).as_ivec3();
+        let v = axis.plane_to_position(0, glam::UVec2::Y).as_ivec3();
+
+        let front_light = self
+            .get_light_or_neighbour(chunk_border, front)
+            .unwrap_or_default();
+        let sample = |offset: glam::IVec3| {
+            let position = front + offset;
+            let occluding = self
+                .get_voxel_or_neighbour(chunk_border, position)
+                .is_some_and(|voxel| registry.is_solid(voxel));
+            let light = (!occluding)
+                .then(|| self.get_light_or_neighbour(chunk_border, position))
+                .flatten();
+            (occluding, light)
+        };
+
+        let corners = [(-1, -1), (1, -1), (1, 1), (-1, 1)].map(|(du, dv)| {
+            let (side1, side1_light) = sample(u * du);
+            let (side2, side2_light) = sample(v * dv);
+            // The corner voxel cannot be seen past two occluding sides
+            let (corner, corner_light) = if side1 && side2 {
+                (true, None)
+            } else {
+                sample(u * du + v * dv)
+            };
+
+            let lights = [Some(front_light), side1_light, side2_light, corner_light];
+            let average = |level: fn(Light) -> u8| {
+                let (sum, count) = lights.iter().flatten().fold((0, 0), |(sum, count), light| {
+                    (sum + level(*light), count + 1)
+                });
+                (sum + count / 2) / count
+            };
+
+            (
+                mesh::vertex_ambient_occlusion(side1, side2, corner),
+                [average(Light::sky), average(Light::block)],
+            )
+        });
+
+        FaceShading {
+            ambient_occlusion: corners.map(|corner| corner.0),
+            light: corners.map(|corner| corner.1),
+        }
+    }
+
+    /// Returns the light at the given position relative to the chunk.
+    ///
+    /// Positions outside of the chunk are looked up in the border copied from the neighbouring
+    /// chunks, returning `None` if the chunk containing the position is not available.
+    fn get_light_or_neighbour(
+        &self,
+        chunk_border: &ChunkBorder,
+        position: glam::IVec3,
+    ) -> Option<Light> {
+        let size = self.size.as_ivec3();
+        let chunk_offset = position.div_euclid(size);
+        let local_position = position.rem_euclid(size).as_uvec3();
+
+        if chunk_offset == glam::IVec3::ZERO {
+            self.light(local_position)
+        } else {
+            chunk_border.light(chunk_offset, local_position)
+        }
+    }
+
+    /// Returns the voxel at the given position relative to the chunk.
+    ///
+    /// Positions outside of the chunk are looked up in the border copied from the neighbouring
+    /// chunks, returning `None` if the chunk containing the position is not available.
+    fn get_voxel_or_neighbour(
+        &self,
+        chunk_border: &ChunkBorder,
+        position: glam::IVec3,
+    ) -> Option<Voxel> {
+        let size = self.size.as_ivec3();
+        let chunk_offset = position.div_euclid(size);
+        let local_position = position.rem_euclid(size).as_uvec3();
+
+        if chunk_offset == glam::IVec3::ZERO {
+            self.get_voxel(local_position).copied()
+        } else {
+            chunk_border.get_voxel(chunk_offset, local_position)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const SIZE: glam::UVec3 = glam::UVec3::splat(4);
+
+    /// Creates a chunk at the origin, surrounded by empty neighbours.
+    fn chunk_with_neighbours(
+        voxels: &[(glam::UVec3, Voxel)],
+    ) -> (Chunk, HashMap<glam::IVec3, Chunk>) {
+        let mut chunk = Chunk::new(glam::IVec3::ZERO, SIZE);
+        for (position, voxel) in voxels {
+            chunk.set_voxel(*position, *voxel);
+        }
+
+        let neighbours = [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .flat_map(|axis| {
+                [Direction::Positive, Direction::Negative]
+                    .map(|direction| axis.get_normal(direction).as_ivec3())
+            })
+            .map(|position| (position, Chunk::new(position, SIZE)))
+            .collect();
+
+        (chunk, neighbours)
+    }
+
+    fn quad_count(mesh: &Option<Mesh>) -> usize {
+        mesh.as_ref().map_or(0, |mesh| mesh.vertices().len() / 4)
+    }
+
+    /// Returns the total area of the quads facing along `normal`, lying in the plane at `layer`.
+    fn face_area(mesh: &Mesh, normal: glam::Vec3, layer: f32) -> f32 {
+        mesh.vertices()
+            .chunks(4)
+            .filter(|quad| glam::Vec3::from(quad[0].normal) == normal)
+            .filter(|quad| glam::Vec3::from(quad[0].position).dot(normal.abs()) == layer)
+            .map(|quad| {
+                let min = quad.iter().fold(glam::Vec3::MAX, |min, vertex| {
+                    min.min(vertex.position.into())
+                });
+                let max = quad.iter().fold(glam::Vec3::MIN, |max, vertex| {
+                    max.max(vertex.position.into())
+                });
+                let extent = (max - min) + normal.abs();
+                extent.x * extent.y * extent.z
+            })
+            .sum()
+    }
+
+    #[test]
+    fn single_voxel_has_six_faces() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[(glam::uvec3(1, 1, 1), stone)]);
+
+        let (solid, transparent) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        assert_eq!(quad_count(&solid), 6);
+        assert_eq!(quad_count(&transparent), 0);
+    }
+
+    #[test]
+    fn identical_faces_are_merged() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let mut voxels = Vec::new();
+        for x in 0..2 {
+            for y in 0..2 {
+                for z in 0..2 {
+                    voxels.push((glam::uvec3(x, y, z), stone));
                 }
+            }
+        }
+        let (chunk, neighbours) = chunk_with_neighbours(&voxels);
+
+        let (solid, _) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        assert_eq!(quad_count(&solid), 6);
+    }
+
+    #[test]
+    fn different_voxels_are_not_merged() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let dirt = registry.by_name("dirt").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(0, 0, 0), stone),
+            (glam::uvec3(1, 0, 0), dirt),
+            (glam::uvec3(2, 0, 0), stone),
+        ]);
+
+        let (solid, _) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        // Three quads on each of the four long sides, and one on each end.
+        assert_eq!(quad_count(&solid), 14);
+    }
+
+    #[test]
+    fn hidden_faces_are_not_merged_into_visible_quads() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(0, 0, 0), stone),
+            (glam::uvec3(1, 0, 0), stone),
+            (glam::uvec3(2, 0, 0), stone),
+            (glam::uvec3(1, 1, 0), stone),
+        ]);
+
+        let (solid, _) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        // The top of the row is split around the covered middle voxel, so only the two outer
+        // voxels expose their top face at y = 1.
+        assert_eq!(quad_count(&solid), 12);
+        let solid = solid.unwrap();
+        assert_eq!(face_area(&solid, glam::Vec3::Y, 1.0), 2.0);
+        assert_eq!(face_area(&solid, glam::Vec3::Y, 2.0), 1.0);
+        assert_eq!(face_area(&solid, glam::Vec3::NEG_Y, 0.0), 3.0);
+    }
+
+    #[test]
+    fn faces_with_different_ambient_occlusion_are_not_merged() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(0, 0, 0), stone),
+            (glam::uvec3(1, 0, 0), stone),
+            (glam::uvec3(2, 0, 0), stone),
+            (glam::uvec3(3, 1, 0), stone),
+        ]);
+
+        for mesher in [Mesher::Greedy, Mesher::Binary] {
+            let solid = chunk
+                .mesh_with(mesher, &ChunkBorder::new(&chunk, &neighbours), &registry)
+                .0
+                .unwrap();
+            let top_quads = solid
+                .vertices()
+                .chunks(4)
+                .filter(|quad| quad[0].normal == [0.0, 1.0, 0.0] && quad[0].position[1] == 1.0)
+                .collect::<Vec<_>>();
+
+            // The top face next to the raised voxel is darkened, so it cannot merge with the rest
+            // of the row.
+            assert_eq!(top_quads.len(), 2);
+            assert_eq!(face_area(&solid, glam::Vec3::Y, 1.0), 3.0);
+            assert!(top_quads
+                .iter()
+                .flat_map(|quad| quad.iter())
+                .any(|vertex| vertex.ambient_occlusion < 1.0));
+        }
+    }
+
+    #[test]
+    fn faces_are_lit_by_the_voxels_in_front_of_them() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let (mut chunk, neighbours) = chunk_with_neighbours(&[(glam::uvec3(1, 0, 1), stone)]);
+        light::light_chunk(&mut chunk, &registry);
+
+        for mesher in [Mesher::Greedy, Mesher::Binary] {
+            let solid = chunk
+                .mesh_with(mesher, &ChunkBorder::new(&chunk, &neighbours), &registry)
+                .0
+                .unwrap();
+            let face_light = |normal: [f32; 3]| {
+                solid
+                    .vertices()
+                    .iter()
+                    .filter(|vertex| vertex.normal == normal)
+                    .map(|vertex| vertex.light)
+                    .collect::<Vec<_>>()
             };
 
-            if let Some(neighbour_voxel) = neighbour_chunk.get_voxel(neighbour_position) {
-                if condition(*neighbour_voxel) {
-                    return true;
+            // The top face is open to the sky, while the bottom face faces the unlit chunk below
+            assert!(face_light([0.0, 1.0, 0.0])
+                .iter()
+                .all(|light| *light == [1.0, 0.0]));
+            assert!(face_light([0.0, -1.0, 0.0])
+                .iter()
+                .all(|light| *light == [0.0, 0.0]));
+        }
+    }
+
+    #[test]
+    fn faces_against_neighbour_chunks_are_culled() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let (chunk, mut neighbours) = chunk_with_neighbours(&[(glam::uvec3(3, 0, 0), stone)]);
+        neighbours
+            .get_mut(&glam::IVec3::X)
+            .unwrap()
+            .set_voxel(glam::uvec3(0, 0, 0), stone);
+
+        let (solid, _) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        assert_eq!(quad_count(&solid), 5);
+    }
+
+    #[test]
+    fn binary_mesher_matches_greedy_mesher() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let dirt = registry.by_name("dirt").unwrap();
+        let water = registry.by_name("water").unwrap();
+        let (chunk, mut neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(0, 0, 0), stone),
+            (glam::uvec3(1, 0, 0), dirt),
+            (glam::uvec3(2, 0, 0), stone),
+            (glam::uvec3(1, 1, 0), stone),
+            (glam::uvec3(3, 3, 3), stone),
+            (glam::uvec3(0, 3, 0), water),
+            (glam::uvec3(1, 3, 0), water),
+        ]);
+        neighbours
+            .get_mut(&glam::IVec3::Z)
+            .unwrap()
+            .set_voxel(glam::uvec3(3, 3, 0), stone);
+
+        let (greedy_solid, greedy_transparent) = chunk.mesh_with(
+            Mesher::Greedy,
+            &ChunkBorder::new(&chunk, &neighbours),
+            &registry,
+        );
+        let (binary_solid, binary_transparent) = chunk.mesh_with(
+            Mesher::Binary,
+            &ChunkBorder::new(&chunk, &neighbours),
+            &registry,
+        );
+
+        assert_eq!(quad_count(&binary_solid), quad_count(&greedy_solid));
+        assert_eq!(
+            quad_count(&binary_transparent),
+            quad_count(&greedy_transparent)
+        );
+    }
+
+    #[test]
+    fn binary_mesher_output_is_stable() {
+        let registry = VoxelRegistry::default();
+        let materials = ["stone", "dirt", "grass"].map(|name| registry.by_name(name).unwrap());
+        let mut voxels = Vec::new();
+        for x in 0..SIZE.x {
+            for y in 0..SIZE.y {
+                for z in 0..SIZE.z {
+                    if (x + y + z) % 4 != 0 {
+                        voxels.push((glam::uvec3(x, y, z), materials[((x * y + z) % 3) as usize]));
+                    }
                 }
             }
         }
+        let (mut chunk, neighbours) = chunk_with_neighbours(&voxels);
+        light::light_chunk(&mut chunk, &registry);
+
+        let vertices = |mesh: Option<Mesh>| {
+            mesh.unwrap()
+                .vertices()
+                .iter()
+                .map(|vertex| {
+                    (
+                        vertex.position,
+                        vertex.normal,
+                        vertex.color,
+                        vertex.ambient_occlusion,
+                        vertex.light,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mesh = || {
+            let border = ChunkBorder::new(&chunk, &neighbours);
+            vertices(chunk.mesh_with(Mesher::Binary, &border, &registry).0)
+        };
+
+        assert_eq!(mesh(), mesh());
+    }
+
+    #[test]
+    fn liquid_faces_are_only_visible_against_air() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let water = registry.by_name("water").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(0, 0, 0), water),
+            (glam::uvec3(1, 0, 0), water),
+            (glam::uvec3(0, 1, 0), stone),
+        ]);
+
+        let (solid, transparent) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        // The top face of the water below the stone is hidden, leaving a single top quad.
+        assert_eq!(quad_count(&transparent), 6);
+        assert_eq!(quad_count(&solid), 6);
+    }
 
-        false
+    #[test]
+    fn plants_are_meshed_as_crosses_without_hiding_faces() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let tall_grass = registry.by_name("tall_grass").unwrap();
+        let (chunk, neighbours) = chunk_with_neighbours(&[
+            (glam::uvec3(1, 1, 1), stone),
+            (glam::uvec3(1, 2, 1), tall_grass),
+        ]);
+
+        let (solid, transparent) = chunk.mesh(&ChunkBorder::new(&chunk, &neighbours), &registry);
+
+        // The stone keeps all of its faces, and the grass adds two quads facing both ways
+        assert_eq!(quad_count(&solid), 6 + 4);
+        assert_eq!(quad_count(&transparent), 0);
     }
 }
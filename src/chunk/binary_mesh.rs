@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+
+use super::{
+    border::ChunkBorder,
+    mesh::{Axis, Direction, FaceShading, Mesh},
+    registry::VoxelRegistry,
+    Chunk, Voxel,
+};
+use crate::utils::coord_to_index;
+
+/// Largest chunk dimension supported by the binary mesher, limited by the width of a column mask.
+pub const MAX_SIZE: u32 = u64::BITS;
+
+/// Bitmasks of every column of voxels along an axis.
+///
+/// Bit `i` of a column is set if the voxel at layer `i` along the axis matches the mask.
+struct ColumnMasks {
+    plane_dimensions: glam::UVec2,
+    columns: Vec<u64>,
+}
+
+impl ColumnMasks {
+    fn new(plane_dimensions: glam::UVec2) -> Self {
+        Self {
+            plane_dimensions,
+            columns: vec![0; plane_dimensions.x as usize * plane_dimensions.y as usize],
+        }
+    }
+
+    fn index(&self, plane: glam::UVec2) -> usize {
+        (plane.x + plane.y * self.plane_dimensions.x) as usize
+    }
+
+    fn set(&mut self, plane: glam::UVec2, layer: u32) {
+        let index = self.index(plane);
+        self.columns[index] |= 1 << layer;
+    }
+
+    fn get(&self, plane: glam::UVec2) -> u64 {
+        self.columns[self.index(plane)]
+    }
+}
+
+/// Quad of visible faces of a slice of the chunk.
+#[derive(Debug, Clone, Copy)]
+struct FaceQuad {
+    axis: Axis,
+    direction: Direction,
+    /// Layer of the slice along the axis.
+    layer: u32,
+    /// Plane position of the first face of the quad.
+    start: glam::UVec2,
+    /// Number of faces covered by the quad along each plane axis.
+    size: glam::UVec2,
+}
+
+/// Returns a mask with the lowest `bits` bits set.
+fn low_bits(bits: u32) -> u64 {
+    if bits >= u64::BITS {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+impl Chunk {
+    /// Meshes the voxels accepted by `voxel_to_mesh` using binary greedy meshing.
+    ///
+    /// Visible faces are found for a whole column of voxels at once by shifting the column masks of
+    /// meshed voxels and open neighbours against each other. The visible faces of every slice are
+    /// then grouped by voxel type into rows of bits, which are merged into quads before they are
+    /// shaded, see [`Chunk::shade_quad`].
+    pub(super) fn binary_mesh<V, N>(
+        &self,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
+        voxel_to_mesh: V,
+        neighbour_condition: N,
+    ) -> Mesh
+    where
+        V: Fn(Voxel) -> bool,
+        N: Fn(Voxel) -> bool,
+    {
+        let mut mesh = Mesh::new();
+
+        if self
+            .voxels
+            .uniform()
+            .is_some_and(|voxel| !voxel_to_mesh(voxel))
+        {
+            return mesh;
+        }
+
+        let voxels = (0..self.size.x as usize * self.size.y as usize * self.size.z as usize)
+            .map(|index| *self.voxels.get(index).expect("index to be within chunk"))
+            .collect::<Vec<_>>();
+
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let depth = axis.get_component(self.size);
+            let plane_dimensions = axis.get_plane(self.size);
+
+            let mut meshed = ColumnMasks::new(plane_dimensions);
+            let mut open = ColumnMasks::new(plane_dimensions);
+            for (index, voxel) in voxels.iter().enumerate() {
+                let position = self.index_to_coord(index);
+                let plane = axis.get_plane(position);
+                let layer = axis.get_component(position);
+
+                if voxel_to_mesh(*voxel) {
+                    meshed.set(plane, layer);
+                }
+                if neighbour_condition(*voxel) {
+                    open.set(plane, layer);
+                }
+            }
+
+            for direction in [Direction::Positive, Direction::Negative] {
+                let normal = axis.get_normal(direction).as_ivec3();
+
+                // Rows of visible faces, per layer and voxel type, in a stable order. Bit `u` of row
+                // `v` is set if the face at plane position `(u, v)` is visible.
+                let mut planes: BTreeMap<(u32, Voxel), Vec<u64>> = BTreeMap::new();
+
+                for v in 0..plane_dimensions.y {
+                    for u in 0..plane_dimensions.x {
+                        let plane = glam::uvec2(u, v);
+                        let column = meshed.get(plane);
+                        if column == 0 {
+                            continue;
+                        }
+
+                        let boundary_layer = match direction {
+                            Direction::Positive => depth - 1,
+                            Direction::Negative => 0,
+                        };
+                        let boundary_open = self
+                            .get_voxel_or_neighbour(
+                                chunk_border,
+                                axis.plane_to_position(boundary_layer, plane).as_ivec3() + normal,
+                            )
+                            .is_some_and(&neighbour_condition);
+
+                        let open_column = open.get(plane);
+                        let open_in_front = match direction {
+                            Direction::Positive => {
+                                (open_column >> 1) | ((boundary_open as u64) << (depth - 1))
+                            }
+                            Direction::Negative => (open_column << 1) | boundary_open as u64,
+                        };
+
+                        let mut faces = column & open_in_front & low_bits(depth);
+                        while faces != 0 {
+                            let layer = faces.trailing_zeros();
+                            faces &= faces - 1;
+
+                            let position = axis.plane_to_position(layer, plane);
+                            let voxel = voxels[coord_to_index(position, self.size)];
+                            planes
+                                .entry((layer, voxel))
+                                .or_insert_with(|| vec![0; plane_dimensions.y as usize])
+                                [v as usize] |= 1 << u;
+                        }
+                    }
+                }
+
+                for ((layer, voxel), mut rows) in planes {
+                    for v in 0..rows.len() {
+                        while rows[v] != 0 {
+                            let u = rows[v].trailing_zeros();
+                            let width = (rows[v] >> u).trailing_ones();
+                            let run = low_bits(width) << u;
+
+                            let mut height = 1;
+                            while v + height < rows.len() && rows[v + height] & run == run {
+                                rows[v + height] &= !run;
+                                height += 1;
+                            }
+                            rows[v] &= !run;
+
+                            let quad = FaceQuad {
+                                axis,
+                                direction,
+                                layer,
+                                start: glam::uvec2(u, v as u32),
+                                size: glam::uvec2(width, height as u32),
+                            };
+                            self.shade_quad(
+                                chunk_border,
+                                registry,
+                                quad,
+                                |FaceQuad { start, size, .. }, shading| {
+                                    let position = axis.plane_to_position(layer, start);
+                                    mesh.add_face(
+                                        position.as_vec3()
+                                            + match direction {
+                                                Direction::Positive => axis.get_normal(direction),
+                                                Direction::Negative => glam::Vec3::ZERO,
+                                            },
+                                        size.as_vec2(),
+                                        axis,
+                                        direction,
+                                        registry.color(voxel),
+                                        shading,
+                                    );
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+
+    /// Shades a quad of faces of the same voxel type, and passes it to `add_quad`.
+    ///
+    /// The whole quad is shaded at once if the voxels in front of it and around it don't occlude
+    /// any of its faces, and are all lit the same. Otherwise, every face is shaded on its own, and
+    /// the quad is split into the largest quads of faces with the same shading.
+    fn shade_quad(
+        &self,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
+        quad: FaceQuad,
+        mut add_quad: impl FnMut(FaceQuad, FaceShading),
+    ) {
+        let FaceQuad { start, size, .. } = quad;
+        let shade_face = |plane: glam::UVec2| {
+            self.face_shading(
+                chunk_border,
+                registry,
+                quad.axis.plane_to_position(quad.layer, plane),
+                quad.axis,
+                quad.direction,
+            )
+        };
+
+        if size == glam::UVec2::ONE || self.is_evenly_lit(chunk_border, registry, quad) {
+            add_quad(quad, shade_face(start));
+            return;
+        }
+
+        let mut shadings = (0..size.y)
+            .flat_map(|v| (0..size.x).map(move |u| glam::uvec2(u, v)))
+            .map(|offset| Some(shade_face(start + offset)))
+            .collect::<Vec<_>>();
+        for v in 0..size.y {
+            let mut u = 0;
+            while u < size.x {
+                let index = (u + v * size.x) as usize;
+                let Some(shading) = shadings[index] else {
+                    u += 1;
+                    continue;
+                };
+
+                let mut quad_size = glam::uvec2(1, 1);
+                while u + quad_size.x < size.x
+                    && shadings[index + quad_size.x as usize] == Some(shading)
+                {
+                    quad_size.x += 1;
+                }
+
+                'outer: while v + quad_size.y < size.y {
+                    let row = index + (quad_size.y * size.x) as usize;
+                    for w in 0..quad_size.x as usize {
+                        if shadings[row + w] != Some(shading) {
+                            break 'outer;
+                        }
+                    }
+                    quad_size.y += 1;
+                }
+
+                for h in 0..quad_size.y {
+                    let row = index + (h * size.x) as usize;
+                    shadings[row..row + quad_size.x as usize].fill(None);
+                }
+
+                let split = FaceQuad {
+                    start: start + glam::uvec2(u, v),
+                    size: quad_size,
+                    ..quad
+                };
+                add_quad(split, shading);
+                u += quad_size.x;
+            }
+        }
+    }
+
+    /// Returns whether the faces of a quad are all shaded the same, because none of the voxels in
+    /// front of the quad and around it are solid, and they are all lit the same.
+    fn is_evenly_lit(
+        &self,
+        chunk_border: &ChunkBorder,
+        registry: &VoxelRegistry,
+        quad: FaceQuad,
+    ) -> bool {
+        let FaceQuad {
+            axis,
+            direction,
+            layer,
+            start,
+            size,
+        } = quad;
+        let front =
+            axis.plane_to_position(layer, start).as_ivec3() + axis.get_normal(direction).as_ivec3();
+        let u_axis = axis.plane_to_position(0, glam::UVec2::X).as_ivec3();
+        let v_axis = axis.plane_to_position(0, glam::UVec2::Y).as_ivec3();
+
+        let mut light = None;
+        for v in -1..=size.y as i32 {
+            for u in -1..=size.x as i32 {
+                let position = front + u_axis * u + v_axis * v;
+                if self
+                    .get_voxel_or_neighbour(chunk_border, position)
+                    .is_some_and(|voxel| registry.is_solid(voxel))
+                {
+                    return false;
+                }
+
+                let Some(voxel_light) = self.get_light_or_neighbour(chunk_border, position) else {
+                    return false;
+                };
+                if *light.get_or_insert(voxel_light) != voxel_light {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    fn index_to_coord(&self, index: usize) -> glam::UVec3 {
+        let index = index as u32;
+        glam::uvec3(
+            index % self.size.x,
+            (index / self.size.x) % self.size.y,
+            index / (self.size.x * self.size.y),
+        )
+    }
+}
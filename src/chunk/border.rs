@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use super::{light::Light, Chunk, Voxel};
+use crate::utils::coord_to_index;
+
+/// Voxels and light of the one voxel thick shell around a chunk, copied from its neighbours.
+///
+/// Meshing a chunk only looks at the voxels directly in front of its faces and around their
+/// corners, so mesh jobs carry this shell instead of copies of all 26 neighbouring chunks.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkBorder {
+    /// Part of the shell taken from every loaded neighbour, keyed by the offset of its grid
+    /// position from the chunk.
+    slabs: HashMap<glam::IVec3, BorderSlab>,
+}
+
+/// Part of the shell of a chunk that lies in one neighbour.
+///
+/// Spans a single voxel along the axes the neighbour is offset on, and the whole chunk along the
+/// others.
+#[derive(Debug, Clone)]
+struct BorderSlab {
+    /// Size of the slab.
+    size: glam::UVec3,
+    /// Size of the neighbour the slab was copied from.
+    chunk_size: glam::UVec3,
+    voxels: Vec<Voxel>,
+    light: Vec<Light>,
+}
+
+impl ChunkBorder {
+    /// Copies the shell around `chunk` from the loaded chunks around it.
+    pub fn new(chunk: &Chunk, chunks: &HashMap<glam::IVec3, Chunk>) -> Self {
+        let mut slabs = HashMap::new();
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let offset = glam::ivec3(x, y, z);
+                    if offset == glam::IVec3::ZERO {
+                        continue;
+                    }
+
+                    if let Some(neighbour) = chunks.get(&(chunk.grid_position + offset)) {
+                        slabs.insert(offset, BorderSlab::new(neighbour, offset));
+                    }
+                }
+            }
+        }
+
+        Self { slabs }
+    }
+
+    /// Returns the voxel at the given position of the neighbour at `offset`.
+    ///
+    /// Returns `None` if the neighbour was not loaded, or the position is not in the shell.
+    pub fn get_voxel(&self, offset: glam::IVec3, position: glam::UVec3) -> Option<Voxel> {
+        let (slab, index) = self.locate(offset, position)?;
+        Some(slab.voxels[index])
+    }
+
+    /// Returns the light at the given position of the neighbour at `offset`.
+    ///
+    /// Returns `None` if the neighbour was not loaded, or the position is not in the shell.
+    pub fn light(&self, offset: glam::IVec3, position: glam::UVec3) -> Option<Light> {
+        let (slab, index) = self.locate(offset, position)?;
+        Some(slab.light[index])
+    }
+
+    /// Returns the slab of the neighbour at `offset`, along with the index of the given position of
+    /// the neighbour within it.
+    fn locate(&self, offset: glam::IVec3, position: glam::UVec3) -> Option<(&BorderSlab, usize)> {
+        let slab = self.slabs.get(&offset)?;
+        let mut slab_position = position;
+        for axis in 0..3 {
+            let layer = match offset[axis] {
+                -1 => slab.chunk_size[axis] - 1,
+                1 => 0,
+                _ => continue,
+            };
+            if position[axis] != layer {
+                return None;
+            }
+            slab_position[axis] = 0;
+        }
+
+        if slab_position.cmpge(slab.size).any() {
+            return None;
+        }
+        Some((slab, coord_to_index(slab_position, slab.size)))
+    }
+}
+
+impl BorderSlab {
+    /// Copies the layer of `neighbour` that touches the chunk it is offset from by `offset`.
+    fn new(neighbour: &Chunk, offset: glam::IVec3) -> Self {
+        let chunk_size = neighbour.size();
+        let size = glam::UVec3::select(
+            offset.cmpeq(glam::IVec3::ZERO),
+            chunk_size,
+            glam::UVec3::ONE,
+        );
+        let origin = glam::UVec3::select(
+            offset.cmplt(glam::IVec3::ZERO),
+            chunk_size - 1,
+            glam::UVec3::ZERO,
+        );
+
+        let len = (size.x * size.y * size.z) as usize;
+        let mut voxels = Vec::with_capacity(len);
+        let mut light = Vec::with_capacity(len);
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    let position = origin + glam::uvec3(x, y, z);
+                    voxels.push(neighbour.get_voxel(position).copied().unwrap_or_default());
+                    light.push(neighbour.light(position).unwrap_or_default());
+                }
+            }
+        }
+
+        Self {
+            size,
+            chunk_size,
+            voxels,
+            light,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{light, registry::VoxelRegistry};
+
+    #[test]
+    fn border_copies_the_layers_touching_the_chunk() {
+        let size = glam::uvec3(4, 5, 6);
+        let voxel = Voxel(1);
+        let chunk = Chunk::new(glam::IVec3::ZERO, size);
+
+        let mut below = Chunk::new(glam::IVec3::NEG_Y, size);
+        below.set_voxel(glam::uvec3(1, 4, 2), voxel);
+        below.set_voxel(glam::uvec3(1, 3, 2), voxel);
+        light::light_chunk(&mut below, &VoxelRegistry::default());
+        let mut corner = Chunk::new(glam::ivec3(1, 1, -1), size);
+        corner.set_voxel(glam::uvec3(0, 0, 5), voxel);
+
+        let chunks = [below.clone(), corner]
+            .into_iter()
+            .map(|chunk| (chunk.grid_position, chunk))
+            .collect();
+        let border = ChunkBorder::new(&chunk, &chunks);
+
+        assert_eq!(
+            border.get_voxel(glam::IVec3::NEG_Y, glam::uvec3(1, 4, 2)),
+            Some(voxel)
+        );
+        assert_eq!(
+            border.get_voxel(glam::IVec3::NEG_Y, glam::uvec3(2, 4, 2)),
+            Some(Voxel::AIR)
+        );
+        assert_eq!(
+            border.light(glam::IVec3::NEG_Y, glam::uvec3(3, 4, 5)),
+            below.light(glam::uvec3(3, 4, 5))
+        );
+        assert_eq!(
+            border.get_voxel(glam::ivec3(1, 1, -1), glam::uvec3(0, 0, 5)),
+            Some(voxel)
+        );
+
+        // Only the layer touching the chunk is copied, and only from loaded neighbours
+        assert_eq!(
+            border.get_voxel(glam::IVec3::NEG_Y, glam::uvec3(1, 3, 2)),
+            None
+        );
+        assert_eq!(
+            border.get_voxel(glam::ivec3(1, 1, -1), glam::uvec3(0, 0, 4)),
+            None
+        );
+        assert_eq!(border.get_voxel(glam::IVec3::Y, glam::uvec3(1, 0, 2)), None);
+    }
+}
@@ -0,0 +1,703 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{registry::VoxelRegistry, Chunk, Voxel};
+use crate::utils::{chunks_around_voxel, coord_to_index, world_to_chunk};
+
+/// Channels of light that propagate independently of each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LightChannel {
+    /// Sunlight reaching the voxel from the sky.
+    Sky,
+    /// Light emitted by voxels.
+    Block,
+}
+
+impl LightChannel {
+    /// Every light channel.
+    pub const ALL: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+}
+
+/// Light level of a voxel in every channel, each from 0 (dark) to [`Light::MAX_LEVEL`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Light(u8);
+
+impl Light {
+    /// Highest light level of a channel.
+    pub const MAX_LEVEL: u8 = 15;
+
+    /// Creates a light from its skylight and block light levels, clamped to the maximum level.
+    pub fn new(sky: u8, block: u8) -> Self {
+        Self(sky.min(Self::MAX_LEVEL) << 4 | block.min(Self::MAX_LEVEL))
+    }
+
+    /// Returns the skylight level.
+    pub fn sky(self) -> u8 {
+        self.0 >> 4
+    }
+
+    /// Returns the block light level.
+    pub fn block(self) -> u8 {
+        self.0 & 0x0f
+    }
+
+    /// Returns the level of the given channel.
+    pub fn get(self, channel: LightChannel) -> u8 {
+        match channel {
+            LightChannel::Sky => self.sky(),
+            LightChannel::Block => self.block(),
+        }
+    }
+
+    /// Returns the light with the level of the given channel replaced.
+    pub fn with(self, channel: LightChannel, level: u8) -> Self {
+        match channel {
+            LightChannel::Sky => Self::new(level, self.block()),
+            LightChannel::Block => Self::new(self.sky(), level),
+        }
+    }
+}
+
+/// Storage for the light of the voxels of a chunk.
+///
+/// Chunks with the same light everywhere, like chunks buried underground or up in the sky, are
+/// stored as a single value.
+#[derive(Debug, Clone)]
+pub enum LightStorage {
+    /// Every voxel in the storage has the same light.
+    Uniform { light: Light, len: usize },
+    /// The light of every voxel is stored separately.
+    Dense(Vec<Light>),
+}
+
+impl LightStorage {
+    /// Creates a new storage of `len` voxels, all lit by `light`.
+    pub fn new(light: Light, len: usize) -> Self {
+        Self::Uniform { light, len }
+    }
+
+    /// Returns the light at the given index.
+    pub fn get(&self, index: usize) -> Option<Light> {
+        match self {
+            Self::Uniform { light, len } => (index < *len).then_some(*light),
+            Self::Dense(lights) => lights.get(index).copied(),
+        }
+    }
+
+    /// Sets the light at the given index.
+    ///
+    /// Out of bounds indices are ignored.
+    pub fn set(&mut self, index: usize, light: Light) {
+        match self {
+            Self::Uniform {
+                light: uniform_light,
+                len,
+            } => {
+                if index >= *len || *uniform_light == light {
+                    return;
+                }
+
+                let mut lights = vec![*uniform_light; *len];
+                lights[index] = light;
+                *self = Self::Dense(lights);
+            }
+            Self::Dense(lights) => {
+                if let Some(current) = lights.get_mut(index) {
+                    *current = light;
+                }
+            }
+        }
+    }
+
+    /// Returns an estimate of the heap memory used by the storage, in bytes.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Self::Uniform { .. } => 0,
+            Self::Dense(lights) => lights.capacity() * std::mem::size_of::<Light>(),
+        }
+    }
+}
+
+/// Directions light spreads in from a voxel.
+const NEIGHBOURS: [glam::IVec3; 6] = [
+    glam::IVec3::X,
+    glam::IVec3::NEG_X,
+    glam::IVec3::Y,
+    glam::IVec3::NEG_Y,
+    glam::IVec3::Z,
+    glam::IVec3::NEG_Z,
+];
+
+/// Returns the light level reaching a voxel from a neighbour lit at `level`.
+///
+/// Light loses one level per voxel, plus the opacity of the voxel it enters. Skylight at full
+/// strength keeps its level while shining straight down through clear voxels, so that open columns
+/// are lit all the way down to the ground.
+fn propagated_level(channel: LightChannel, level: u8, downwards: bool, opacity: u8) -> u8 {
+    if opacity >= Light::MAX_LEVEL {
+        0
+    } else if channel == LightChannel::Sky && downwards && level == Light::MAX_LEVEL && opacity == 0
+    {
+        Light::MAX_LEVEL
+    } else {
+        level.saturating_sub(1 + opacity)
+    }
+}
+
+/// Flood fills light through the voxels of the loaded chunks.
+///
+/// Positions are in world coordinates. Light never spreads into chunks that are not loaded, except
+/// that the sky above a chunk whose upper neighbour is not loaded is assumed to be open.
+struct LightEngine<'a> {
+    chunks: &'a mut HashMap<glam::IVec3, Chunk>,
+    registry: &'a VoxelRegistry,
+    chunk_size: glam::UVec3,
+    /// Voxels whose light spreads to their neighbours, per channel.
+    additions: [VecDeque<glam::IVec3>; 2],
+    /// Voxels whose light was removed, along with their previous level, per channel.
+    removals: [VecDeque<(glam::IVec3, u8)>; 2],
+    /// Grid positions of the chunks whose meshes depend on light that changed.
+    changed: HashSet<glam::IVec3>,
+}
+
+impl<'a> LightEngine<'a> {
+    fn new(
+        chunks: &'a mut HashMap<glam::IVec3, Chunk>,
+        registry: &'a VoxelRegistry,
+        chunk_size: glam::UVec3,
+    ) -> Self {
+        Self {
+            chunks,
+            registry,
+            chunk_size,
+            additions: Default::default(),
+            removals: Default::default(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// Splits a position into the grid position of its chunk and its position within the chunk.
+    fn locate(&self, position: glam::IVec3) -> (glam::IVec3, glam::UVec3) {
+        world_to_chunk(position, self.chunk_size)
+    }
+
+    /// Returns the voxel and light at the given position, if its chunk is loaded.
+    fn get(&self, position: glam::IVec3) -> Option<(Voxel, Light)> {
+        let (grid_position, local_position) = self.locate(position);
+        let chunk = self.chunks.get(&grid_position)?;
+        let index = coord_to_index(local_position, chunk.size);
+        Some((*chunk.voxels.get(index)?, chunk.light.get(index)?))
+    }
+
+    fn set_level(&mut self, channel: LightChannel, position: glam::IVec3, level: u8) {
+        let (grid_position, local_position) = self.locate(position);
+        let Some(chunk) = self.chunks.get_mut(&grid_position) else {
+            return;
+        };
+
+        let index = coord_to_index(local_position, chunk.size);
+        if let Some(light) = chunk.light.get(index) {
+            if light.get(channel) != level {
+                chunk.light.set(index, light.with(channel, level));
+                self.changed
+                    .extend(chunks_around_voxel(position, self.chunk_size));
+            }
+        }
+    }
+
+    /// Returns the light level the voxel at the given position produces by itself.
+    ///
+    /// Emissive voxels produce block light, and voxels at the top of a chunk without a loaded chunk
+    /// above it receive skylight from the open sky.
+    fn source_level(&self, channel: LightChannel, position: glam::IVec3, voxel: Voxel) -> u8 {
+        match channel {
+            LightChannel::Block => self.registry.emission(voxel),
+            LightChannel::Sky => {
+                let (grid_position, _) = self.locate(position);
+                let (above, _) = self.locate(position + glam::IVec3::Y);
+                if above != grid_position && !self.chunks.contains_key(&above) {
+                    propagated_level(
+                        channel,
+                        Light::MAX_LEVEL,
+                        true,
+                        self.registry.opacity(voxel),
+                    )
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Lights the voxel at the given position by its own source, if it produces any light.
+    fn seed(&mut self, channel: LightChannel, position: glam::IVec3) {
+        let Some((voxel, light)) = self.get(position) else {
+            return;
+        };
+
+        let level = self.source_level(channel, position, voxel);
+        if level > light.get(channel) {
+            self.set_level(channel, position, level);
+            self.additions[channel as usize].push_back(position);
+        }
+    }
+
+    /// Queues the voxel at the given position to spread its light to its neighbours.
+    fn spread_from(&mut self, channel: LightChannel, position: glam::IVec3) {
+        if self
+            .get(position)
+            .is_some_and(|(_, light)| light.get(channel) > 0)
+        {
+            self.additions[channel as usize].push_back(position);
+        }
+    }
+
+    /// Queues the voxels on either side of a face of the chunk at the given grid position to spread
+    /// their light across it, if they would brighten the voxel on the other side.
+    fn spread_across_face(&mut self, grid_position: glam::IVec3, offset: glam::IVec3) {
+        let (Some(inside), Some(outside)) = (
+            self.chunks.get(&grid_position),
+            self.chunks.get(&(grid_position + offset)),
+        ) else {
+            return;
+        };
+
+        let size = self.chunk_size.as_ivec3();
+        let normal_axis = if offset.x != 0 {
+            0
+        } else if offset.y != 0 {
+            1
+        } else {
+            2
+        };
+        let (u_axis, v_axis) = ((normal_axis + 1) % 3, (normal_axis + 2) % 3);
+        let origin = grid_position * size;
+
+        for u in 0..size[u_axis] {
+            for v in 0..size[v_axis] {
+                let mut position = glam::IVec3::ZERO;
+                position[u_axis] = u;
+                position[v_axis] = v;
+                if offset[normal_axis] > 0 {
+                    position[normal_axis] = size[normal_axis] - 1;
+                }
+                let inside_index = coord_to_index(position.as_uvec3(), inside.size);
+                let outside_index = coord_to_index(
+                    (position + offset).rem_euclid(size).as_uvec3(),
+                    outside.size,
+                );
+
+                let (
+                    Some(inside_voxel),
+                    Some(inside_light),
+                    Some(outside_voxel),
+                    Some(outside_light),
+                ) = (
+                    inside.voxels.get(inside_index),
+                    inside.light.get(inside_index),
+                    outside.voxels.get(outside_index),
+                    outside.light.get(outside_index),
+                )
+                else {
+                    continue;
+                };
+
+                for channel in LightChannel::ALL {
+                    let outwards = propagated_level(
+                        channel,
+                        inside_light.get(channel),
+                        offset.y < 0,
+                        self.registry.opacity(*outside_voxel),
+                    );
+                    if outwards > outside_light.get(channel) {
+                        self.additions[channel as usize].push_back(origin + position);
+                    }
+
+                    let inwards = propagated_level(
+                        channel,
+                        outside_light.get(channel),
+                        offset.y > 0,
+                        self.registry.opacity(*inside_voxel),
+                    );
+                    if inwards > inside_light.get(channel) {
+                        self.additions[channel as usize].push_back(origin + position + offset);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the light of the voxel at the given position, along with the light that may have
+    /// spread from it.
+    fn remove(&mut self, channel: LightChannel, position: glam::IVec3) {
+        let Some((_, light)) = self.get(position) else {
+            return;
+        };
+
+        let level = light.get(channel);
+        if level > 0 {
+            self.set_level(channel, position, 0);
+            self.removals[channel as usize].push_back((position, level));
+        }
+    }
+
+    /// Removes the light that spread from the voxels queued for removal.
+    ///
+    /// Neighbours that are at least as bright as a removed voxel are lit by something else, and are
+    /// queued to spread their light back into the darkened voxels.
+    fn propagate_removals(&mut self) {
+        for channel in LightChannel::ALL {
+            while let Some((position, level)) = self.removals[channel as usize].pop_front() {
+                for offset in NEIGHBOURS {
+                    let neighbour = position + offset;
+                    let Some((voxel, light)) = self.get(neighbour) else {
+                        continue;
+                    };
+
+                    let neighbour_level = light.get(channel);
+                    if neighbour_level == 0 {
+                        continue;
+                    }
+
+                    let lit_straight_down = channel == LightChannel::Sky
+                        && offset == glam::IVec3::NEG_Y
+                        && level == Light::MAX_LEVEL
+                        && neighbour_level == Light::MAX_LEVEL;
+                    if neighbour_level < level || lit_straight_down {
+                        self.set_level(channel, neighbour, 0);
+                        self.removals[channel as usize].push_back((neighbour, neighbour_level));
+
+                        let source_level = self.source_level(channel, neighbour, voxel);
+                        if source_level > 0 {
+                            self.set_level(channel, neighbour, source_level);
+                            self.additions[channel as usize].push_back(neighbour);
+                        }
+                    } else {
+                        self.additions[channel as usize].push_back(neighbour);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spreads the light of the voxels queued for addition through their surroundings.
+    fn propagate_additions(&mut self) {
+        for channel in LightChannel::ALL {
+            while let Some(position) = self.additions[channel as usize].pop_front() {
+                let Some((_, light)) = self.get(position) else {
+                    continue;
+                };
+
+                let level = light.get(channel);
+                if level <= 1 {
+                    continue;
+                }
+
+                for offset in NEIGHBOURS {
+                    let neighbour = position + offset;
+                    let Some((voxel, neighbour_light)) = self.get(neighbour) else {
+                        continue;
+                    };
+
+                    let neighbour_level = propagated_level(
+                        channel,
+                        level,
+                        offset == glam::IVec3::NEG_Y,
+                        self.registry.opacity(voxel),
+                    );
+                    if neighbour_level > neighbour_light.get(channel) {
+                        self.set_level(channel, neighbour, neighbour_level);
+                        self.additions[channel as usize].push_back(neighbour);
+                    }
+                }
+            }
+        }
+    }
+
+    fn propagate(&mut self) {
+        self.propagate_removals();
+        self.propagate_additions();
+    }
+}
+
+/// Calls `f` with the world position of every voxel of the chunk at the given grid position.
+fn for_each_position(
+    grid_position: glam::IVec3,
+    size: glam::UVec3,
+    mut f: impl FnMut(glam::IVec3),
+) {
+    let origin = grid_position * size.as_ivec3();
+    for x in 0..size.x as i32 {
+        for y in 0..size.y as i32 {
+            for z in 0..size.z as i32 {
+                f(origin + glam::ivec3(x, y, z));
+            }
+        }
+    }
+}
+
+/// Lights a chunk on its own, as if it had open sky above it and darkness around it.
+///
+/// This is the expensive part of lighting a new chunk, and can run before the chunk is added to the
+/// world. Once it is, [`light_borders`] merges its light with the light of its neighbours.
+pub fn light_chunk(chunk: &mut Chunk, registry: &VoxelRegistry) {
+    let len = chunk.size.x as usize * chunk.size.y as usize * chunk.size.z as usize;
+    if chunk.is_empty() {
+        chunk.light = LightStorage::new(Light::new(Light::MAX_LEVEL, 0), len);
+        return;
+    }
+    chunk.light = LightStorage::new(Light::default(), len);
+
+    let grid_position = chunk.grid_position;
+    let size = chunk.size;
+    let mut chunks = HashMap::from([(
+        grid_position,
+        std::mem::replace(chunk, Chunk::new(grid_position, size)),
+    )]);
+
+    let mut engine = LightEngine::new(&mut chunks, registry, size);
+    for_each_position(grid_position, size, |position| {
+        for channel in LightChannel::ALL {
+            engine.seed(channel, position);
+        }
+    });
+    engine.propagate();
+
+    *chunk = chunks
+        .remove(&grid_position)
+        .expect("to get the lit chunk back");
+}
+
+/// Merges the light of a newly added chunk, lit by [`light_chunk`], with the light of the loaded
+/// chunks around it.
+///
+/// Skylight that was assumed to come from the open sky, either in the new chunk or in the chunk
+/// below it, is removed where the chunk above turns out to cover it.
+///
+/// Returns the grid positions of the chunks whose meshes depend on light that changed.
+pub fn light_borders(
+    chunks: &mut HashMap<glam::IVec3, Chunk>,
+    registry: &VoxelRegistry,
+    grid_position: glam::IVec3,
+) -> HashSet<glam::IVec3> {
+    let Some(size) = chunks.get(&grid_position).map(|chunk| chunk.size) else {
+        return HashSet::new();
+    };
+
+    let mut engine = LightEngine::new(chunks, registry, size);
+    let size = size.as_ivec3();
+    let origin = grid_position * size;
+
+    // Voxels at the top of the new chunk, and at the top of the chunk below it, are lit as if the
+    // sky was open above them
+    for top in [origin.y + size.y - 1, origin.y - 1] {
+        for x in 0..size.x {
+            for z in 0..size.z {
+                let position = glam::ivec3(origin.x + x, top, origin.z + z);
+                let (Some((voxel, light)), Some((_, above))) =
+                    (engine.get(position), engine.get(position + glam::IVec3::Y))
+                else {
+                    continue;
+                };
+
+                let opacity = registry.opacity(voxel);
+                let from_sky = propagated_level(LightChannel::Sky, Light::MAX_LEVEL, true, opacity);
+                let from_above = propagated_level(LightChannel::Sky, above.sky(), true, opacity);
+                if light.sky() > from_above && light.sky() >= from_sky {
+                    engine.remove(LightChannel::Sky, position);
+                }
+            }
+        }
+        engine.propagate_removals();
+    }
+
+    // Let light cross every face of the chunk, in both directions
+    for offset in NEIGHBOURS {
+        engine.spread_across_face(grid_position, offset);
+    }
+    engine.propagate();
+
+    engine.changed
+}
+
+/// Updates the light around voxels that changed in the loaded chunks.
+///
+/// Returns the grid positions of the chunks whose meshes depend on light that changed.
+pub fn relight(
+    chunks: &mut HashMap<glam::IVec3, Chunk>,
+    registry: &VoxelRegistry,
+    positions: impl IntoIterator<Item = glam::IVec3>,
+) -> HashSet<glam::IVec3> {
+    let Some(size) = chunks.values().next().map(|chunk| chunk.size) else {
+        return HashSet::new();
+    };
+
+    let positions = positions.into_iter().collect::<Vec<_>>();
+    let mut engine = LightEngine::new(chunks, registry, size);
+    for &position in &positions {
+        for channel in LightChannel::ALL {
+            engine.remove(channel, position);
+        }
+    }
+    engine.propagate_removals();
+
+    for &position in &positions {
+        for channel in LightChannel::ALL {
+            engine.seed(channel, position);
+            for offset in NEIGHBOURS {
+                engine.spread_from(channel, position + offset);
+            }
+        }
+    }
+    engine.propagate_additions();
+
+    engine.changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: glam::UVec3 = glam::UVec3::splat(8);
+
+    fn light_at(chunks: &HashMap<glam::IVec3, Chunk>, position: glam::IVec3) -> Light {
+        let (grid_position, local_position) = world_to_chunk(position, SIZE);
+        chunks[&grid_position]
+            .light(local_position)
+            .expect("position to be within chunk")
+    }
+
+    /// Lights the given chunks and adds them to the world one after the other.
+    fn add_chunks(
+        chunks: &mut HashMap<glam::IVec3, Chunk>,
+        registry: &VoxelRegistry,
+        new_chunks: impl IntoIterator<Item = Chunk>,
+    ) {
+        for mut chunk in new_chunks {
+            light_chunk(&mut chunk, registry);
+            let grid_position = chunk.grid_position;
+            chunks.insert(grid_position, chunk);
+            light_borders(chunks, registry, grid_position);
+        }
+    }
+
+    #[test]
+    fn skylight_shines_down_until_blocked() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let mut chunk = Chunk::new(glam::IVec3::ZERO, SIZE);
+        for x in 0..4 {
+            for z in 0..SIZE.z {
+                chunk.set_voxel(glam::uvec3(x, 4, z), stone);
+            }
+        }
+
+        let mut chunks = HashMap::new();
+        add_chunks(&mut chunks, &registry, [chunk]);
+
+        // Open columns are fully lit down to the bottom, covered ones only by light from the side
+        assert_eq!(light_at(&chunks, glam::ivec3(6, 0, 3)).sky(), 15);
+        assert_eq!(light_at(&chunks, glam::ivec3(3, 3, 3)).sky(), 14);
+        assert_eq!(light_at(&chunks, glam::ivec3(0, 3, 3)).sky(), 11);
+        assert_eq!(light_at(&chunks, glam::ivec3(2, 4, 3)).sky(), 0);
+    }
+
+    #[test]
+    fn loading_a_chunk_above_removes_assumed_skylight() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let mut roof = Chunk::new(glam::IVec3::Y, SIZE);
+        for x in 0..SIZE.x {
+            for z in 0..SIZE.z {
+                roof.set_voxel(glam::uvec3(x, 0, z), stone);
+            }
+        }
+
+        let mut chunks = HashMap::new();
+        add_chunks(
+            &mut chunks,
+            &registry,
+            [Chunk::new(glam::IVec3::ZERO, SIZE)],
+        );
+        assert_eq!(light_at(&chunks, glam::ivec3(3, 3, 3)).sky(), 15);
+
+        add_chunks(&mut chunks, &registry, [roof]);
+        assert_eq!(light_at(&chunks, glam::ivec3(3, 3, 3)).sky(), 0);
+    }
+
+    #[test]
+    fn block_light_falls_off_across_chunk_borders() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let lamp = registry.by_name("lamp").unwrap();
+
+        // Stone slabs above keep the skylight out
+        let mut chunks = HashMap::new();
+        let roofs = [glam::ivec3(0, 1, 0), glam::ivec3(1, 1, 0)].map(|grid_position| {
+            let mut roof = Chunk::new(grid_position, SIZE);
+            for x in 0..SIZE.x {
+                for z in 0..SIZE.z {
+                    roof.set_voxel(glam::uvec3(x, 0, z), stone);
+                }
+            }
+            roof
+        });
+        let mut lit = Chunk::new(glam::IVec3::ZERO, SIZE);
+        lit.set_voxel(glam::uvec3(6, 3, 3), lamp);
+        add_chunks(
+            &mut chunks,
+            &registry,
+            roofs
+                .into_iter()
+                .chain([lit, Chunk::new(glam::IVec3::X, SIZE)]),
+        );
+
+        assert_eq!(light_at(&chunks, glam::ivec3(6, 3, 3)).block(), 15);
+        assert_eq!(light_at(&chunks, glam::ivec3(7, 3, 3)).block(), 14);
+        assert_eq!(light_at(&chunks, glam::ivec3(8, 3, 3)).block(), 13);
+        assert_eq!(light_at(&chunks, glam::ivec3(10, 4, 3)).block(), 10);
+        assert_eq!(light_at(&chunks, glam::ivec3(10, 4, 3)).sky(), 0);
+    }
+
+    #[test]
+    fn relighting_follows_edits() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let lamp = registry.by_name("lamp").unwrap();
+        let mut chunks = HashMap::new();
+        add_chunks(
+            &mut chunks,
+            &registry,
+            [Chunk::new(glam::IVec3::ZERO, SIZE)],
+        );
+
+        // Covering a column darkens the voxels below it
+        let position = glam::ivec3(3, 5, 3);
+        chunks
+            .get_mut(&glam::IVec3::ZERO)
+            .unwrap()
+            .set_voxel(position.as_uvec3(), stone);
+        let changed = relight(&mut chunks, &registry, [position]);
+        assert!(changed.contains(&glam::IVec3::ZERO));
+        assert_eq!(light_at(&chunks, glam::ivec3(3, 2, 3)).sky(), 14);
+        assert_eq!(light_at(&chunks, position).sky(), 0);
+
+        // Placing a lamp lights its surroundings, and removing it darkens them again
+        let lamp_position = glam::ivec3(1, 1, 1);
+        let chunk = chunks.get_mut(&glam::IVec3::ZERO).unwrap();
+        chunk.set_voxel(lamp_position.as_uvec3(), lamp);
+        relight(&mut chunks, &registry, [lamp_position]);
+        assert_eq!(light_at(&chunks, glam::ivec3(1, 1, 4)).block(), 12);
+
+        let chunk = chunks.get_mut(&glam::IVec3::ZERO).unwrap();
+        chunk.set_voxel(lamp_position.as_uvec3(), Voxel::AIR);
+        relight(&mut chunks, &registry, [lamp_position]);
+        assert_eq!(light_at(&chunks, glam::ivec3(1, 1, 4)).block(), 0);
+        assert_eq!(light_at(&chunks, lamp_position).sky(), 15);
+
+        // Removing the cover lets the sky back in
+        let chunk = chunks.get_mut(&glam::IVec3::ZERO).unwrap();
+        chunk.set_voxel(position.as_uvec3(), Voxel::AIR);
+        relight(&mut chunks, &registry, [position]);
+        assert_eq!(light_at(&chunks, glam::ivec3(3, 2, 3)).sky(), 15);
+    }
+}
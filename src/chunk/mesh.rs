@@ -1,14 +1,52 @@
+use super::light::Light;
+
 /// Vertex definition for the voxel shader.
 #[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
     pub color: [f32; 4],
+    /// Brightness of the vertex after ambient occlusion, from 0 to 1.
+    pub ambient_occlusion: f32,
+    /// Skylight and block light reaching the vertex, from 0 to 1.
+    pub light: [f32; 2],
+}
+implement_vertex!(Vertex, position, normal, color, ambient_occlusion, light);
+
+/// Ambient occlusion levels of the corners of a face, from 0 (fully occluded) to 3 (unoccluded).
+///
+/// Corners are ordered `(0, 0)`, `(1, 0)`, `(1, 1)`, `(0, 1)` in the plane coordinates of the face,
+/// see [`Axis::get_plane`].
+pub type AmbientOcclusion = [u8; 4];
+
+/// Skylight and block light levels of the corners of a face, from 0 to
+/// [`Light::MAX_LEVEL`].
+///
+/// Corners are ordered like [`AmbientOcclusion`].
+pub type FaceLight = [[u8; 2]; 4];
+
+/// Shading of the corners of a face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FaceShading {
+    pub ambient_occlusion: AmbientOcclusion,
+    pub light: FaceLight,
+}
+
+/// Brightness of a vertex for each ambient occlusion level.
+const AMBIENT_OCCLUSION_BRIGHTNESS: [f32; 4] = [0.35, 0.55, 0.75, 1.0];
+
+/// Returns the ambient occlusion level of a vertex, given whether the two voxels along the edges of
+/// the vertex and the voxel in the corner between them are occluding.
+pub fn vertex_ambient_occlusion(side1: bool, side2: bool, corner: bool) -> u8 {
+    if side1 && side2 {
+        0
+    } else {
+        3 - (side1 as u8 + side2 as u8 + corner as u8)
+    }
 }
-implement_vertex!(Vertex, position, normal, color);
 
 /// Cardinal axes of the Cartesian coordinate system.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Axis {
     X,
     Y,
@@ -33,16 +71,47 @@ impl Axis {
             },
         }
     }
+
+    /// Returns the component of the vector along the axis.
+    pub fn get_component(&self, vector: glam::UVec3) -> u32 {
+        match self {
+            Axis::X => vector.x,
+            Axis::Y => vector.y,
+            Axis::Z => vector.z,
+        }
+    }
+
+    /// Projects the vector onto the plane perpendicular to the axis.
+    ///
+    /// The plane coordinates are `(y, z)` for the X axis, `(x, z)` for the Y axis and `(x, y)` for
+    /// the Z axis, matching the size passed to [`Mesh::add_face`].
+    pub fn get_plane(&self, vector: glam::UVec3) -> glam::UVec2 {
+        match self {
+            Axis::X => glam::uvec2(vector.y, vector.z),
+            Axis::Y => glam::uvec2(vector.x, vector.z),
+            Axis::Z => glam::uvec2(vector.x, vector.y),
+        }
+    }
+
+    /// Converts a layer along the axis and plane coordinates back into a position.
+    pub fn plane_to_position(&self, layer: u32, plane: glam::UVec2) -> glam::UVec3 {
+        match self {
+            Axis::X => glam::uvec3(layer, plane.x, plane.y),
+            Axis::Y => glam::uvec3(plane.x, layer, plane.y),
+            Axis::Z => glam::uvec3(plane.x, plane.y, layer),
+        }
+    }
 }
 
 /// Direction of the axis.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Positive,
     Negative,
 }
 
 /// Represents the mesh of a chunk.
+#[derive(Default)]
 pub struct Mesh {
     vertices: Vec<Vertex>,
     indices: Vec<u32>,
@@ -57,6 +126,11 @@ impl Mesh {
         }
     }
 
+    /// Returns the vertices of the mesh.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
     /// Returns whether the mesh is empty.
     pub fn is_empty(&self) -> bool {
         self.vertices.is_empty() || self.indices.is_empty()
@@ -83,43 +157,55 @@ impl Mesh {
     }
 
     /// Adds a quad to the mesh.
-    pub fn add_quad<P, N, C>(&mut self, p1: P, p2: P, p3: P, p4: P, normal: N, color: C)
-    where
+    ///
+    /// The quad is split into two triangles along the diagonal with the brightest corners, so that
+    /// ambient occlusion and light are interpolated symmetrically across the quad.
+    pub fn add_quad<P, N, C>(
+        &mut self,
+        positions: [P; 4],
+        normal: N,
+        color: C,
+        ambient_occlusion: [f32; 4],
+        light: [[f32; 2]; 4],
+    ) where
         P: Into<[f32; 3]>,
         N: Into<[f32; 3]> + Copy,
         C: Into<[f32; 4]> + Copy,
     {
         let start_index = self.vertices.len() as u32;
-        self.vertices.extend(&[
-            Vertex {
-                position: p1.into(),
-                normal: normal.into(),
-                color: color.into(),
-            },
-            Vertex {
-                position: p2.into(),
-                normal: normal.into(),
-                color: color.into(),
-            },
-            Vertex {
-                position: p3.into(),
-                normal: normal.into(),
-                color: color.into(),
-            },
-            Vertex {
-                position: p4.into(),
-                normal: normal.into(),
-                color: color.into(),
-            },
-        ]);
-        self.indices.extend(&[
-            start_index,
-            start_index + 1,
-            start_index + 2,
-            start_index,
-            start_index + 2,
-            start_index + 3,
-        ]);
+        self.vertices
+            .extend(positions.into_iter().zip(ambient_occlusion).zip(light).map(
+                |((position, ambient_occlusion), light)| Vertex {
+                    position: position.into(),
+                    normal: normal.into(),
+                    color: color.into(),
+                    ambient_occlusion,
+                    light,
+                },
+            ));
+
+        let brightness = |corner: usize| {
+            ambient_occlusion[corner] * (1.0 + light[corner][0].max(light[corner][1]))
+        };
+        if brightness(0) + brightness(2) < brightness(1) + brightness(3) {
+            self.indices.extend(&[
+                start_index + 1,
+                start_index + 2,
+                start_index + 3,
+                start_index + 1,
+                start_index + 3,
+                start_index,
+            ]);
+        } else {
+            self.indices.extend(&[
+                start_index,
+                start_index + 1,
+                start_index + 2,
+                start_index,
+                start_index + 2,
+                start_index + 3,
+            ]);
+        }
     }
 
     /// Creates a quad facing the given axis and direction, and adds it to the mesh.
@@ -130,59 +216,104 @@ impl Mesh {
         axis: Axis,
         direction: Direction,
         color: C,
+        shading: FaceShading,
     ) where
         C: Into<[f32; 4]> + Copy,
     {
-        let vertices = match (axis, direction) {
-            (Axis::X, Direction::Positive) => [
-                [position.x, position.y, position.z + size.y],
-                [position.x, position.y + size.x, position.z + size.y],
-                [position.x, position.y + size.x, position.z],
-                [position.x, position.y, position.z],
-            ],
-
-            (Axis::X, Direction::Negative) => [
-                [position.x, position.y, position.z],
-                [position.x, position.y + size.x, position.z],
-                [position.x, position.y + size.x, position.z + size.y],
-                [position.x, position.y, position.z + size.y],
-            ],
-            (Axis::Y, Direction::Positive) => [
-                [position.x, position.y, position.z],
-                [position.x + size.x, position.y, position.z],
-                [position.x + size.x, position.y, position.z + size.y],
-                [position.x, position.y, position.z + size.y],
-            ],
-
-            (Axis::Y, Direction::Negative) => [
-                [position.x, position.y, position.z],
-                [position.x, position.y, position.z + size.y],
-                [position.x + size.x, position.y, position.z + size.y],
-                [position.x + size.x, position.y, position.z],
-            ],
-
-            (Axis::Z, Direction::Positive) => [
-                [position.x, position.y, position.z],
-                [position.x, position.y + size.y, position.z],
-                [position.x + size.x, position.y + size.y, position.z],
-                [position.x + size.x, position.y, position.z],
-            ],
-
-            (Axis::Z, Direction::Negative) => [
-                [position.x, position.y, position.z],
-                [position.x + size.x, position.y, position.z],
-                [position.x + size.x, position.y + size.y, position.z],
-                [position.x, position.y + size.y, position.z],
-            ],
+        // Corner of the face, in plane coordinates, that each vertex lies on
+        let (vertices, corners) = match (axis, direction) {
+            (Axis::X, Direction::Positive) => (
+                [
+                    [position.x, position.y, position.z + size.y],
+                    [position.x, position.y + size.x, position.z + size.y],
+                    [position.x, position.y + size.x, position.z],
+                    [position.x, position.y, position.z],
+                ],
+                [3, 2, 1, 0],
+            ),
+
+            (Axis::X, Direction::Negative) => (
+                [
+                    [position.x, position.y, position.z],
+                    [position.x, position.y + size.x, position.z],
+                    [position.x, position.y + size.x, position.z + size.y],
+                    [position.x, position.y, position.z + size.y],
+                ],
+                [0, 1, 2, 3],
+            ),
+            (Axis::Y, Direction::Positive) => (
+                [
+                    [position.x, position.y, position.z],
+                    [position.x + size.x, position.y, position.z],
+                    [position.x + size.x, position.y, position.z + size.y],
+                    [position.x, position.y, position.z + size.y],
+                ],
+                [0, 1, 2, 3],
+            ),
+
+            (Axis::Y, Direction::Negative) => (
+                [
+                    [position.x, position.y, position.z],
+                    [position.x, position.y, position.z + size.y],
+                    [position.x + size.x, position.y, position.z + size.y],
+                    [position.x + size.x, position.y, position.z],
+                ],
+                [0, 3, 2, 1],
+            ),
+
+            (Axis::Z, Direction::Positive) => (
+                [
+                    [position.x, position.y, position.z],
+                    [position.x, position.y + size.y, position.z],
+                    [position.x + size.x, position.y + size.y, position.z],
+                    [position.x + size.x, position.y, position.z],
+                ],
+                [0, 3, 2, 1],
+            ),
+
+            (Axis::Z, Direction::Negative) => (
+                [
+                    [position.x, position.y, position.z],
+                    [position.x + size.x, position.y, position.z],
+                    [position.x + size.x, position.y + size.y, position.z],
+                    [position.x, position.y + size.y, position.z],
+                ],
+                [0, 1, 2, 3],
+            ),
         };
 
         self.add_quad(
-            vertices[0],
-            vertices[1],
-            vertices[2],
-            vertices[3],
+            vertices,
             axis.get_normal(direction),
             color,
+            corners.map(|corner: usize| {
+                AMBIENT_OCCLUSION_BRIGHTNESS[shading.ambient_occlusion[corner] as usize]
+            }),
+            corners.map(|corner: usize| {
+                shading.light[corner].map(|level| level as f32 / Light::MAX_LEVEL as f32)
+            }),
         );
     }
+
+    /// Adds two diagonal quads crossing inside the voxel at the given position, visible from both
+    /// sides, for voxels drawn as sprites instead of blocks.
+    ///
+    /// The quads are lit like the top of a block by the light of the voxel itself.
+    pub fn add_cross<C>(&mut self, position: glam::Vec3, color: C, light: [u8; 2])
+    where
+        C: Into<[f32; 4]> + Copy,
+    {
+        let light = [light.map(|level| level as f32 / Light::MAX_LEVEL as f32); 4];
+        for (start, end) in [
+            (glam::vec3(0.0, 0.0, 0.0), glam::vec3(1.0, 0.0, 1.0)),
+            (glam::vec3(1.0, 0.0, 0.0), glam::vec3(0.0, 0.0, 1.0)),
+        ] {
+            let (start, end) = (position + start, position + end);
+            let corners = [start, end, end + glam::Vec3::Y, start + glam::Vec3::Y];
+            self.add_quad(corners, glam::Vec3::Y, color, [1.0; 4], light);
+
+            let [a, b, c, d] = corners;
+            self.add_quad([a, d, c, b], glam::Vec3::Y, color, [1.0; 4], light);
+        }
+    }
 }
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Voxel, VoxelColor};
+
+/// Name of the implicitly registered air voxel.
+const AIR_NAME: &str = "air";
+
+/// Definition of a single voxel type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoxelDefinition {
+    /// Unique name of the voxel type.
+    pub name: String,
+    /// Color of the voxel.
+    pub color: VoxelColor,
+    /// Whether the voxel is a solid block.
+    #[serde(default)]
+    pub solid: bool,
+    /// Whether the voxel is a liquid.
+    #[serde(default)]
+    pub liquid: bool,
+    /// How much light is absorbed when passing through the voxel, from 0 to 15.
+    #[serde(default)]
+    pub opacity: u8,
+    /// Light level emitted by the voxel, from 0 to 15.
+    #[serde(default)]
+    pub emission: u8,
+}
+
+impl VoxelDefinition {
+    fn air() -> Self {
+        Self {
+            name: AIR_NAME.to_string(),
+            color: [0.0, 0.0, 0.0, 0.0],
+            solid: false,
+            liquid: false,
+            opacity: 0,
+            emission: 0,
+        }
+    }
+}
+
+/// Errors that can occur while loading a voxel registry.
+#[derive(Debug)]
+pub enum VoxelRegistryError {
+    /// The definition file could not be read.
+    Io(std::io::Error),
+    /// The definition file is not valid.
+    Parse(ron::error::SpannedError),
+    /// Two voxel types share the same name.
+    DuplicateName(String),
+    /// More voxel types were defined than fit into a voxel identifier.
+    TooManyVoxels,
+    /// A voxel type placed by the world generator is not defined.
+    MissingVoxel(String),
+}
+
+impl fmt::Display for VoxelRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read voxel definitions: {error}"),
+            Self::Parse(error) => write!(f, "failed to parse voxel definitions: {error}"),
+            Self::DuplicateName(name) => write!(f, "voxel `{name}` is defined more than once"),
+            Self::TooManyVoxels => write!(f, "too many voxel types defined"),
+            Self::MissingVoxel(name) => write!(f, "voxel `{name}` is not defined"),
+        }
+    }
+}
+
+impl std::error::Error for VoxelRegistryError {}
+
+/// Registry of every voxel type known to the world.
+///
+/// Voxel types are loaded from a definition file, and assigned compact numeric identifiers in the
+/// order they are defined. Air is always registered as [`Voxel::AIR`].
+#[derive(Debug, Clone)]
+pub struct VoxelRegistry {
+    definitions: Vec<VoxelDefinition>,
+    names: HashMap<String, Voxel>,
+}
+
+impl VoxelRegistry {
+    /// Creates a registry from the given voxel definitions.
+    pub fn new(definitions: Vec<VoxelDefinition>) -> Result<Self, VoxelRegistryError> {
+        let mut registry = Self {
+            definitions: Vec::with_capacity(definitions.len() + 1),
+            names: HashMap::new(),
+        };
+
+        for definition in std::iter::once(VoxelDefinition::air()).chain(definitions) {
+            let id = u16::try_from(registry.definitions.len())
+                .map_err(|_| VoxelRegistryError::TooManyVoxels)?;
+            if registry.names.contains_key(&definition.name) {
+                return Err(VoxelRegistryError::DuplicateName(definition.name));
+            }
+
+            registry.names.insert(definition.name.clone(), Voxel(id));
+            registry.definitions.push(definition);
+        }
+
+        Ok(registry)
+    }
+
+    /// Creates a registry from the contents of a RON definition file.
+    pub fn from_ron(source: &str) -> Result<Self, VoxelRegistryError> {
+        let definitions = ron::from_str(source).map_err(VoxelRegistryError::Parse)?;
+        Self::new(definitions)
+    }
+
+    /// Loads a registry from the RON definition file at the given path.
+    ///
+    /// The file must define every voxel placed by the world generator, see
+    /// [`GENERATED_VOXELS`](crate::generation::GENERATED_VOXELS).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VoxelRegistryError> {
+        let source = std::fs::read_to_string(path).map_err(VoxelRegistryError::Io)?;
+        let registry = Self::from_ron(&source)?;
+
+        if let Some(name) = crate::generation::GENERATED_VOXELS
+            .into_iter()
+            .find(|name| registry.by_name(name).is_none())
+        {
+            return Err(VoxelRegistryError::MissingVoxel(name.to_string()));
+        }
+
+        Ok(registry)
+    }
+
+    /// Returns the definition of the given voxel.
+    ///
+    /// Unknown voxels are treated as air.
+    pub fn get(&self, voxel: Voxel) -> &VoxelDefinition {
+        self.definitions
+            .get(voxel.0 as usize)
+            .unwrap_or(&self.definitions[0])
+    }
+
+    /// Returns every registered voxel along with its definition, starting with air.
+    pub fn voxels(&self) -> impl Iterator<Item = (Voxel, &VoxelDefinition)> {
+        self.definitions
+            .iter()
+            .enumerate()
+            .map(|(id, definition)| (Voxel(id as u16), definition))
+    }
+
+    /// Returns the voxel registered with the given name.
+    pub fn by_name(&self, name: &str) -> Option<Voxel> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns the color of the given voxel.
+    pub fn color(&self, voxel: Voxel) -> VoxelColor {
+        self.get(voxel).color
+    }
+
+    /// Returns whether the given voxel is a solid block.
+    pub fn is_solid(&self, voxel: Voxel) -> bool {
+        self.get(voxel).solid
+    }
+
+    /// Returns whether the given voxel is a liquid.
+    pub fn is_liquid(&self, voxel: Voxel) -> bool {
+        self.get(voxel).liquid
+    }
+
+    /// Returns how much light is absorbed when passing through the given voxel.
+    pub fn opacity(&self, voxel: Voxel) -> u8 {
+        self.get(voxel).opacity
+    }
+
+    /// Returns the light level emitted by the given voxel.
+    pub fn emission(&self, voxel: Voxel) -> u8 {
+        self.get(voxel).emission
+    }
+}
+
+impl Default for VoxelRegistry {
+    /// Creates a registry from the voxel definitions bundled with the executable.
+    fn default() -> Self {
+        Self::from_ron(include_str!("../../assets/voxels.ron"))
+            .expect("to parse bundled voxel definitions")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let result = VoxelRegistry::from_ron(
+            r#"[
+                (name: "stone", color: (0.5, 0.5, 0.5, 1.0), solid: true),
+                (name: "stone", color: (0.2, 0.2, 0.2, 1.0)),
+            ]"#,
+        );
+        assert!(matches!(result, Err(VoxelRegistryError::DuplicateName(name)) if name == "stone"));
+
+        // Air is registered implicitly, so it cannot be defined again
+        let result = VoxelRegistry::from_ron(r#"[(name: "air", color: (0.0, 0.0, 0.0, 0.0))]"#);
+        assert!(matches!(result, Err(VoxelRegistryError::DuplicateName(name)) if name == "air"));
+    }
+
+    #[test]
+    fn loaded_registries_define_every_generated_voxel() {
+        let path =
+            std::env::temp_dir().join(format!("voxel-registry-test-{}.ron", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[(name: "stone", color: (0.5, 0.5, 0.5, 1.0), solid: true)]"#,
+        )
+        .unwrap();
+        let result = VoxelRegistry::load(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(result, Err(VoxelRegistryError::MissingVoxel(name)) if name == "grass"));
+
+        let bundled = VoxelRegistry::default();
+        for name in crate::generation::GENERATED_VOXELS {
+            assert!(bundled.by_name(name).is_some(), "`{name}` is not bundled");
+        }
+    }
+
+    #[test]
+    fn air_is_always_the_first_voxel() {
+        let registry = VoxelRegistry::new(Vec::new()).unwrap();
+        assert_eq!(registry.by_name("air"), Some(Voxel::AIR));
+        assert_eq!(registry.voxels().count(), 1);
+
+        let registry = VoxelRegistry::default();
+        assert_eq!(registry.by_name("air"), Some(Voxel::AIR));
+        assert_eq!(
+            registry.voxels().next().map(|(voxel, _)| voxel),
+            Some(Voxel::AIR)
+        );
+        assert!(!registry.is_solid(Voxel::AIR));
+    }
+
+    #[test]
+    fn voxels_are_looked_up_by_name_and_id() {
+        let registry = VoxelRegistry::from_ron(
+            r#"[
+                (name: "stone", color: (0.5, 0.5, 0.5, 1.0), solid: true),
+                (name: "water", color: (0.0, 0.0, 1.0, 0.5), liquid: true),
+            ]"#,
+        )
+        .unwrap();
+
+        let stone = registry.by_name("stone").unwrap();
+        let water = registry.by_name("water").unwrap();
+        assert_eq!(stone, Voxel(1));
+        assert_eq!(water, Voxel(2));
+        assert_eq!(registry.by_name("lava"), None);
+
+        assert_eq!(registry.color(stone), [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(registry.color(water), [0.0, 0.0, 1.0, 0.5]);
+        assert!(registry.is_solid(stone) && !registry.is_liquid(stone));
+        assert!(!registry.is_solid(water) && registry.is_liquid(water));
+
+        // Unknown voxels are treated as air
+        assert_eq!(registry.get(Voxel(3)).name, "air");
+        assert!(!registry.is_solid(Voxel(3)));
+    }
+}
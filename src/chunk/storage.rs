@@ -0,0 +1,307 @@
+use super::Voxel;
+
+/// Palette-compressed storage for the voxels of a chunk.
+///
+/// Chunks that consist of a single voxel type (e.g. all air or all stone) are stored as a single
+/// value. All other chunks store a palette of the distinct voxels, and a bit-packed index into that
+/// palette for every voxel.
+#[derive(Debug, Clone)]
+pub enum VoxelStorage {
+    /// Every voxel in the storage is the same.
+    Uniform { voxel: Voxel, len: usize },
+    /// Voxels are stored as bit-packed indices into a palette.
+    Paletted(PalettedStorage),
+}
+
+impl VoxelStorage {
+    /// Creates a new storage of `len` voxels, all set to `voxel`.
+    pub fn new(voxel: Voxel, len: usize) -> Self {
+        Self::Uniform { voxel, len }
+    }
+
+    /// Returns the number of voxels in the storage.
+    fn len(&self) -> usize {
+        match self {
+            Self::Uniform { len, .. } => *len,
+            Self::Paletted(storage) => storage.len,
+        }
+    }
+
+    /// Returns the voxel that fills the entire storage, if there is one.
+    pub fn uniform(&self) -> Option<Voxel> {
+        match self {
+            Self::Uniform { voxel, .. } => Some(*voxel),
+            Self::Paletted(_) => None,
+        }
+    }
+
+    /// Returns an estimate of the heap memory used by the storage, in bytes.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Self::Uniform { .. } => 0,
+            Self::Paletted(storage) => {
+                storage.palette.capacity() * std::mem::size_of::<PaletteEntry>()
+                    + storage.data.capacity() * std::mem::size_of::<u64>()
+            }
+        }
+    }
+
+    /// Returns a reference to the voxel at the given index.
+    pub fn get(&self, index: usize) -> Option<&Voxel> {
+        if index >= self.len() {
+            return None;
+        }
+
+        match self {
+            Self::Uniform { voxel, .. } => Some(voxel),
+            Self::Paletted(storage) => Some(storage.get(index)),
+        }
+    }
+
+    /// Sets the voxel at the given index.
+    ///
+    /// Out of bounds indices are ignored.
+    pub fn set(&mut self, index: usize, voxel: Voxel) {
+        if index >= self.len() {
+            return;
+        }
+
+        match self {
+            Self::Uniform {
+                voxel: uniform_voxel,
+                len,
+            } => {
+                if *uniform_voxel == voxel {
+                    return;
+                }
+
+                let mut storage = PalettedStorage::new(*uniform_voxel, *len);
+                storage.set(index, voxel);
+                *self = Self::Paletted(storage);
+            }
+            Self::Paletted(storage) => {
+                storage.set(index, voxel);
+
+                if let Some(voxel) = storage.uniform() {
+                    *self = Self::Uniform {
+                        voxel,
+                        len: storage.len,
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// A single entry of a palette, with the number of voxels that reference it.
+#[derive(Debug, Clone, Copy)]
+struct PaletteEntry {
+    voxel: Voxel,
+    count: usize,
+}
+
+/// Bit-packed palette storage.
+///
+/// Indices never straddle two words, so each `u64` holds `64 / bits_per_index` indices.
+#[derive(Debug, Clone)]
+pub struct PalettedStorage {
+    palette: Vec<PaletteEntry>,
+    bits_per_index: u32,
+    data: Vec<u64>,
+    len: usize,
+}
+
+impl PalettedStorage {
+    fn new(voxel: Voxel, len: usize) -> Self {
+        let bits_per_index = 1;
+
+        Self {
+            palette: vec![PaletteEntry { voxel, count: len }],
+            bits_per_index,
+            data: vec![0; Self::words_for(len, bits_per_index)],
+            len,
+        }
+    }
+
+    fn words_for(len: usize, bits_per_index: u32) -> usize {
+        let indices_per_word = (u64::BITS / bits_per_index) as usize;
+        len.div_ceil(indices_per_word)
+    }
+
+    fn uniform(&self) -> Option<Voxel> {
+        self.palette
+            .iter()
+            .find(|entry| entry.count == self.len)
+            .map(|entry| entry.voxel)
+    }
+
+    fn get(&self, index: usize) -> &Voxel {
+        &self.palette[self.palette_index(index)].voxel
+    }
+
+    fn set(&mut self, index: usize, voxel: Voxel) {
+        let old_palette_index = self.palette_index(index);
+        if self.palette[old_palette_index].voxel == voxel {
+            return;
+        }
+
+        let new_palette_index = self.palette_index_for(voxel);
+        self.palette[old_palette_index].count -= 1;
+        self.palette[new_palette_index].count += 1;
+        self.set_palette_index(index, new_palette_index);
+    }
+
+    /// Returns the palette index of the given voxel, inserting it into the palette if necessary.
+    fn palette_index_for(&mut self, voxel: Voxel) -> usize {
+        if let Some(index) = self.palette.iter().position(|entry| entry.voxel == voxel) {
+            return index;
+        }
+
+        // Reuse a palette entry that is no longer referenced by any voxel
+        if let Some(index) = self.palette.iter().position(|entry| entry.count == 0) {
+            self.palette[index].voxel = voxel;
+            return index;
+        }
+
+        self.palette.push(PaletteEntry { voxel, count: 0 });
+        if self.palette.len() > 1 << self.bits_per_index {
+            self.resize(self.bits_per_index + 1);
+        }
+
+        self.palette.len() - 1
+    }
+
+    fn resize(&mut self, bits_per_index: u32) {
+        let mut resized = Self {
+            palette: Vec::new(),
+            bits_per_index,
+            data: vec![0; Self::words_for(self.len, bits_per_index)],
+            len: self.len,
+        };
+
+        for index in 0..self.len {
+            resized.set_palette_index(index, self.palette_index(index));
+        }
+
+        self.bits_per_index = resized.bits_per_index;
+        self.data = resized.data;
+    }
+
+    fn palette_index(&self, index: usize) -> usize {
+        let (word, shift) = self.locate(index);
+        let mask = (1u64 << self.bits_per_index) - 1;
+        ((self.data[word] >> shift) & mask) as usize
+    }
+
+    fn set_palette_index(&mut self, index: usize, palette_index: usize) {
+        let (word, shift) = self.locate(index);
+        let mask = (1u64 << self.bits_per_index) - 1;
+        self.data[word] = (self.data[word] & !(mask << shift)) | ((palette_index as u64) << shift);
+    }
+
+    fn locate(&self, index: usize) -> (usize, u32) {
+        let indices_per_word = (u64::BITS / self.bits_per_index) as usize;
+        let word = index / indices_per_word;
+        let shift = (index % indices_per_word) as u32 * self.bits_per_index;
+        (word, shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEN: usize = 16 * 16 * 16;
+
+    fn paletted(storage: &VoxelStorage) -> &PalettedStorage {
+        match storage {
+            VoxelStorage::Paletted(storage) => storage,
+            VoxelStorage::Uniform { .. } => panic!("storage is uniform"),
+        }
+    }
+
+    #[test]
+    fn palette_grows_across_bit_widths() {
+        let mut storage = VoxelStorage::new(Voxel(0), LEN);
+
+        // Every new voxel past a power of two needs one more bit per index
+        for (id, bits_per_index) in [(1, 1), (2, 2), (3, 2), (4, 3), (8, 4), (16, 5), (32, 6)] {
+            for voxel in 1..=id {
+                storage.set(voxel as usize * 7, Voxel(voxel));
+            }
+            assert_eq!(
+                paletted(&storage).bits_per_index,
+                bits_per_index,
+                "{id} voxels"
+            );
+
+            for voxel in 1..=id {
+                assert_eq!(storage.get(voxel as usize * 7), Some(&Voxel(voxel)));
+            }
+            assert_eq!(storage.get(1), Some(&Voxel(0)));
+        }
+    }
+
+    #[test]
+    fn paletted_storage_round_trips_voxels() {
+        let mut storage = VoxelStorage::new(Voxel(0), LEN);
+        let voxel_at = |index: usize| Voxel((index * 31 % 11) as u16);
+
+        for index in 0..LEN {
+            storage.set(index, voxel_at(index));
+        }
+        for index in 0..LEN {
+            assert_eq!(storage.get(index), Some(&voxel_at(index)), "at {index}");
+        }
+
+        // Overwriting every other voxel leaves the rest untouched
+        for index in (0..LEN).step_by(2) {
+            storage.set(index, Voxel(12));
+        }
+        for index in 0..LEN {
+            let expected = if index % 2 == 0 {
+                Voxel(12)
+            } else {
+                voxel_at(index)
+            };
+            assert_eq!(storage.get(index), Some(&expected), "at {index}");
+        }
+    }
+
+    #[test]
+    fn out_of_bounds_indices_are_ignored() {
+        let mut storage = VoxelStorage::new(Voxel(1), LEN);
+        assert_eq!(storage.get(LEN), None);
+        storage.set(LEN, Voxel(2));
+        assert_eq!(storage.uniform(), Some(Voxel(1)));
+
+        storage.set(0, Voxel(2));
+        assert_eq!(storage.get(LEN), None);
+        storage.set(LEN, Voxel(3));
+        assert!(paletted(&storage)
+            .palette
+            .iter()
+            .all(|entry| entry.voxel != Voxel(3)));
+    }
+
+    #[test]
+    fn storage_collapses_back_to_uniform() {
+        let mut storage = VoxelStorage::new(Voxel(0), LEN);
+        storage.set(5, Voxel(1));
+        storage.set(6, Voxel(2));
+        assert_eq!(storage.uniform(), None);
+        assert!(storage.heap_size() > 0);
+
+        storage.set(5, Voxel(0));
+        assert_eq!(storage.uniform(), None);
+        storage.set(6, Voxel(0));
+        assert_eq!(storage.uniform(), Some(Voxel(0)));
+        assert_eq!(storage.heap_size(), 0);
+
+        // Filling the whole storage with another voxel collapses it as well
+        for index in 0..LEN {
+            storage.set(index, Voxel(4));
+        }
+        assert_eq!(storage.uniform(), Some(Voxel(4)));
+    }
+}
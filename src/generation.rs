@@ -3,16 +3,29 @@ use noise::{
     Add, Cache, Clamp, Curve, Fbm, Min, MultiFractal, NoiseFn, Perlin, RidgedMulti, ScaleBias,
     Seedable, Select, Terrace, Turbulence,
 };
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use crate::chunk::{Chunk, Voxel};
+use crate::chunk::{registry::VoxelRegistry, Chunk, Voxel};
 
+use self::biomes::Biome;
+
+pub mod biomes;
+pub mod caves;
+pub mod density;
+pub mod features;
 pub mod hills;
 pub mod mountains;
+pub mod ores;
 pub mod plains;
+pub mod presets;
+pub mod preview;
 pub mod rivers;
+pub mod structures;
 
 /// Defines options that control the creation of a noise module for world generation.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WorldGenerationOptions {
     /// Base seed for noise modules.
     pub seed: u32,
@@ -45,6 +58,48 @@ pub struct WorldGenerationOptions {
     pub plain_options: plains::PlainOptions,
     /// Options for generating rivers.
     pub river_options: rivers::RiverOptions,
+    /// Options for the 3D density noise applied on top of the terrain height.
+    pub density_options: density::DensityOptions,
+    /// Options for carving caves.
+    pub cave_options: caves::CaveOptions,
+    /// Options for placing biomes.
+    pub biome_options: biomes::BiomeOptions,
+    /// Options for placing ore veins.
+    pub ore_options: ores::OreOptions,
+    /// Options for growing trees and vegetation.
+    pub feature_options: features::FeatureOptions,
+    /// Options for placing structures.
+    pub structure_options: structures::StructureOptions,
+}
+
+impl Default for WorldGenerationOptions {
+    fn default() -> Self {
+        Self {
+            seed: 1337,
+            chunk_size: glam::UVec3::splat(32),
+            max_height: 128,
+            dirt_layer_thickness: 5,
+
+            continent_frequency: 0.001,
+            continent_lacunarity: 2.208984375,
+            continent_height_scale: 0.25,
+            sea_level: 0.0,
+
+            shelf_level: -0.375,
+            terrain_offset: 1.0,
+
+            mountain_options: mountains::MountainOptions::default(),
+            hill_options: hills::HillOptions::default(),
+            plain_options: plains::PlainOptions::default(),
+            river_options: rivers::RiverOptions::default(),
+            density_options: density::DensityOptions::default(),
+            cave_options: caves::CaveOptions::default(),
+            biome_options: biomes::BiomeOptions::default(),
+            ore_options: ores::OreOptions::default(),
+            feature_options: features::FeatureOptions::default(),
+            structure_options: structures::StructureOptions::default(),
+        }
+    }
 }
 
 impl WorldGenerationOptions {
@@ -53,7 +108,7 @@ impl WorldGenerationOptions {
         let continent_with_plains = || {
             let add = Add::new(
                 self.continent_elevation(),
-                self.plain_options.as_noise_module(&self),
+                self.plain_options.as_noise_module(self),
             );
             Cache::new(add)
         };
@@ -61,7 +116,7 @@ impl WorldGenerationOptions {
         let continent_with_hills = || {
             let add = Add::new(
                 continent_with_plains(),
-                self.hill_options.as_noise_module(&self),
+                self.hill_options.as_noise_module(self),
             );
 
             let select = Select::new(continent_with_plains(), add, self.terrain_definition())
@@ -77,7 +132,7 @@ impl WorldGenerationOptions {
         let continent_with_mountains = || {
             let add = Add::new(
                 self.continent_elevation(),
-                self.mountain_options.as_noise_module(&self),
+                self.mountain_options.as_noise_module(self),
             );
 
             let curve = Curve::new(self.continent_definition())
@@ -98,21 +153,17 @@ impl WorldGenerationOptions {
             Cache::new(select)
         };
 
-        let continent_with_rivers = {
-            let scaled = ScaleBias::new(self.river_options.as_noise_module(&self))
-                .set_scale(self.river_options.depth / 2.0)
-                .set_bias(-self.river_options.depth / 2.0);
-
-            let add = Add::new(continent_with_mountains(), scaled);
+        let scaled = ScaleBias::new(self.river_options.as_noise_module(self))
+            .set_scale(self.river_options.depth / 2.0)
+            .set_bias(-self.river_options.depth / 2.0);
 
-            let select = Select::new(continent_with_mountains(), add, self.terrain_definition())
-                .set_bounds(self.sea_level, self.continent_height_scale + self.sea_level)
-                .set_falloff(self.continent_height_scale - self.sea_level);
+        let add = Add::new(continent_with_mountains(), scaled);
 
-            Cache::new(select)
-        };
+        let select = Select::new(continent_with_mountains(), add, self.terrain_definition())
+            .set_bounds(self.sea_level, self.continent_height_scale + self.sea_level)
+            .set_falloff(self.continent_height_scale - self.sea_level);
 
-        continent_with_rivers
+        Cache::new(select)
     }
 
     fn base_continent_definition(&self) -> impl NoiseFn<f64, 2> {
@@ -149,7 +200,7 @@ impl WorldGenerationOptions {
     }
 
     fn continent_definition(&self) -> impl NoiseFn<f64, 2> {
-        let tu0 = Turbulence::<_, Perlin>::new(Self::base_continent_definition(&self))
+        let tu0 = Turbulence::<_, Perlin>::new(Self::base_continent_definition(self))
             .set_seed(self.seed + 10)
             .set_frequency(self.continent_frequency * 15.25)
             .set_power(self.continent_frequency / 113.75)
@@ -168,9 +219,9 @@ impl WorldGenerationOptions {
             .set_roughness(11);
 
         let select = Select::new(
-            Self::base_continent_definition(&self),
+            Self::base_continent_definition(self),
             tu2,
-            Self::base_continent_definition(&self),
+            Self::base_continent_definition(self),
         )
         .set_bounds(self.sea_level - 0.0375, self.sea_level + 1000.0375)
         .set_falloff(0.0625);
@@ -180,7 +231,7 @@ impl WorldGenerationOptions {
 
     fn continent_elevation(&self) -> impl NoiseFn<f64, 2> {
         let continental_shelf = {
-            let te = Terrace::new(Self::continent_definition(&self))
+            let te = Terrace::new(Self::continent_definition(self))
                 .add_control_point(-1.0)
                 .add_control_point(-0.75)
                 .add_control_point(self.shelf_level)
@@ -202,23 +253,19 @@ impl WorldGenerationOptions {
             Cache::new(add)
         };
 
-        let continent_elevation = {
-            let scaled = ScaleBias::new(Self::continent_definition(&self))
-                .set_scale(self.continent_height_scale)
-                .set_bias(0.0);
+        let scaled = ScaleBias::new(Self::continent_definition(self))
+            .set_scale(self.continent_height_scale)
+            .set_bias(0.0);
 
-            let select = Select::new(scaled, continental_shelf, Self::continent_definition(&self))
-                .set_bounds(self.shelf_level - 1000.0, self.shelf_level)
-                .set_falloff(0.03125);
+        let select = Select::new(scaled, continental_shelf, Self::continent_definition(self))
+            .set_bounds(self.shelf_level - 1000.0, self.shelf_level)
+            .set_falloff(0.03125);
 
-            Cache::new(select)
-        };
-
-        continent_elevation
+        Cache::new(select)
     }
 
     fn terrain_definition(&self) -> impl NoiseFn<f64, 2> {
-        let tu = Turbulence::<_, Perlin>::new(Self::continent_definition(&self))
+        let tu = Turbulence::<_, Perlin>::new(Self::continent_definition(self))
             .set_seed(self.seed + 20)
             .set_frequency(self.continent_frequency * 18.125)
             .set_power(self.continent_frequency / 20.59375 * self.terrain_offset)
@@ -234,6 +281,33 @@ impl WorldGenerationOptions {
 }
 
 impl WorldGenerationOptions {
+    /// Smallest distance kept between the continental shelf and the sea level.
+    const MIN_SHELF_DEPTH: f64 = 0.001;
+
+    /// Adjusts options that are outside of their valid range to the nearest valid value.
+    ///
+    /// Chunk sizes and the maximum height must be non-zero, the continent frequency must be
+    /// positive, and the continental shelf must stay below the sea level.
+    pub fn sanitize(&mut self) {
+        self.chunk_size = self.chunk_size.max(glam::UVec3::ONE);
+        self.max_height = self.max_height.max(1);
+        self.continent_frequency = self.continent_frequency.max(f64::EPSILON);
+        self.shelf_level = self.shelf_level.min(self.sea_level - Self::MIN_SHELF_DEPTH);
+    }
+
+    /// Returns a hash of every option, telling apart the worlds generated with different options.
+    ///
+    /// The hash only depends on the options, so it is the same across runs and builds. Options
+    /// added in later versions change the hash of every world.
+    pub fn fingerprint(&self) -> u64 {
+        let source = ron::to_string(self).expect("to serialize world generation options");
+
+        // 64-bit FNV-1a
+        source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
     /// Returns the height of the sea level in voxels.
     pub fn sea_level_voxels(&self) -> i32 {
         self.sea_level
@@ -242,54 +316,556 @@ impl WorldGenerationOptions {
     }
 }
 
+/// Names of every voxel placed by the world generator, which registries loaded from a file must
+/// define, see [`VoxelRegistry::load`].
+pub const GENERATED_VOXELS: [&str; 13] = [
+    "stone",
+    "grass",
+    "dirt",
+    "sand",
+    "snow",
+    "water",
+    "coal_ore",
+    "iron_ore",
+    "gold_ore",
+    "diamond_ore",
+    "wood",
+    "leaves",
+    "tall_grass",
+];
+
+/// Surface voxels of a biome.
+#[derive(Clone, Copy)]
+struct BiomeVoxels {
+    top: Voxel,
+    filler: Voxel,
+}
+
+/// Voxels placed by the terrain generator, resolved from a [`VoxelRegistry`].
+struct TerrainVoxels {
+    stone: Voxel,
+    grass: Voxel,
+    sand: Voxel,
+    snow: Voxel,
+    water: Voxel,
+    /// Surface voxels of every biome, indexed by [`Biome`].
+    biomes: [BiomeVoxels; Biome::ALL.len()],
+    /// Voxels of every ore, in the order of [`ores::OreOptions::veins`].
+    ores: Vec<Voxel>,
+    features: features::FeatureVoxels,
+}
+
+impl TerrainVoxels {
+    fn new(options: &WorldGenerationOptions, registry: &VoxelRegistry) -> Self {
+        let voxel = |name: &str| {
+            registry
+                .by_name(name)
+                .unwrap_or_else(|| panic!("voxel registry to define `{name}`"))
+        };
+
+        Self {
+            stone: voxel("stone"),
+            grass: voxel("grass"),
+            sand: voxel("sand"),
+            snow: voxel("snow"),
+            water: voxel("water"),
+            biomes: Biome::ALL.map(|biome| {
+                let (top, filler) = biome.materials();
+                BiomeVoxels {
+                    top: voxel(top),
+                    filler: voxel(filler),
+                }
+            }),
+            ores: options
+                .ore_options
+                .veins()
+                .iter()
+                .map(|(name, _)| voxel(name))
+                .collect(),
+            features: features::FeatureVoxels {
+                wood: voxel("wood"),
+                leaves: voxel("leaves"),
+                tall_grass: voxel("tall_grass"),
+            },
+        }
+    }
+}
+
+impl TerrainVoxels {
+    /// Returns the solid voxel at height `y`, `depth` voxels below the surface of the terrain.
+    ///
+    /// The surface of dry land is covered by the voxels of the biome of the column, or by snow above
+    /// the snow line.
+    fn layer_voxel(
+        &self,
+        options: &WorldGenerationOptions,
+        climate: Climate,
+        depth: u32,
+        y: i32,
+    ) -> Voxel {
+        let is_underwater = y <= options.sea_level_voxels();
+        let biome = self.biomes[climate.biome as usize];
+
+        if depth > options.dirt_layer_thickness {
+            self.stone
+        } else if is_underwater {
+            self.sand
+        } else if depth > 0 {
+            biome.filler
+        } else if options
+            .biome_options
+            .is_snow_capped(options, climate.temperature, y)
+        {
+            self.snow
+        } else {
+            biome.top
+        }
+    }
+}
+
+impl WorldGenerationOptions {
+    /// Returns the height of the terrain surface in the given column of the world, in voxels.
+    ///
+    /// This is the height of the 2D heightfield, before the 3D density noise is applied.
+    pub fn terrain_height(&self, noise_module: &impl NoiseFn<f64, 2>, x: f64, z: f64) -> i32 {
+        noise_module
+            .get([x, z])
+            .remap(-1.0, 1.0, 0.0, self.max_height as f64)
+            .floor() as i32
+    }
+}
+
+/// Climate and biome of a column of the world.
+#[derive(Debug, Clone, Copy)]
+struct Climate {
+    temperature: f64,
+    biome: Biome,
+}
+
+/// Creates a random number generator seeded from the world seed and a position, so that anything
+/// placed from it is the same regardless of the order chunks are generated in.
+///
+/// `salt` tells apart the generators of different features at the same position.
+fn position_rng(options: &WorldGenerationOptions, salt: u32, position: glam::IVec3) -> StdRng {
+    let mut hash = ((options.seed as u64) << 32) | salt as u64;
+    for coordinate in position.to_array() {
+        // SplitMix64 finalizer, mixing every coordinate into the hash
+        hash ^= coordinate as u32 as u64;
+        hash = hash.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+    }
+
+    StdRng::seed_from_u64(hash)
+}
+
+/// Samples the shape of the terrain, combining the 2D heightfield with the 3D density noise.
+struct TerrainSampler<'a> {
+    options: &'a WorldGenerationOptions,
+    height: Box<dyn NoiseFn<f64, 2>>,
+    density: Box<dyn NoiseFn<f64, 3>>,
+    caves: Box<dyn NoiseFn<f64, 3>>,
+    temperature: Box<dyn NoiseFn<f64, 2>>,
+    humidity: Box<dyn NoiseFn<f64, 2>>,
+}
+
+impl<'a> TerrainSampler<'a> {
+    fn new(options: &'a WorldGenerationOptions) -> Self {
+        Self {
+            options,
+            height: Box::new(options.as_noise_module()),
+            density: Box::new(options.density_options.as_noise_module(options)),
+            caves: Box::new(options.cave_options.as_noise_module(options)),
+            temperature: Box::new(options.biome_options.temperature_noise_module(options)),
+            humidity: Box::new(options.biome_options.humidity_noise_module(options)),
+        }
+    }
+
+    /// Returns the climate and biome of the given column, whose surface is at `height`.
+    fn climate(&self, x: f64, z: f64, height: i32) -> Climate {
+        let temperature = self.temperature.get([x, z]);
+        let humidity = self.humidity.get([x, z]);
+
+        Climate {
+            temperature,
+            biome: self
+                .options
+                .biome_options
+                .biome(self.options, temperature, humidity, height),
+        }
+    }
+
+    /// Returns the height of the 2D heightfield in the given column, in voxels.
+    fn terrain_height(&self, x: f64, z: f64) -> i32 {
+        self.options.terrain_height(&self.height, x, z)
+    }
+
+    /// Returns the distance, in voxels, the density noise can move the surface from the terrain
+    /// height.
+    fn max_offset(&self) -> i32 {
+        self.options.density_options.amplitude.max(0.0).ceil() as i32
+    }
+
+    /// Returns whether the voxel at the given position is solid, in a column whose heightfield is
+    /// at `terrain_height`.
+    fn is_solid(&self, position: glam::IVec3, terrain_height: i32) -> bool {
+        let depth = terrain_height - position.y;
+
+        // The density noise can't make voxels far enough from the heightfield flip
+        let max_offset = self.max_offset();
+        if depth > max_offset {
+            return true;
+        } else if depth < -max_offset {
+            return false;
+        }
+
+        let offset = self.density.get(position.as_dvec3().to_array())
+            * self.options.density_options.amplitude;
+        depth as f64 + offset >= 0.0
+    }
+
+    /// Returns whether the solid voxel at the given position is carved into a cave.
+    ///
+    /// `depth` is the depth of the voxel below the surface of the terrain, and `underwater` whether
+    /// that surface is covered by water.
+    fn is_cave(&self, position: glam::IVec3, depth: u32, underwater: bool) -> bool {
+        if underwater && depth < self.options.cave_options.ocean_floor_thickness {
+            return false;
+        }
+
+        self.caves.get(position.as_dvec3().to_array()) > 0.0
+    }
+
+    /// Returns the height of the topmost solid voxel in the given column.
+    fn surface_height(&self, x: i32, z: i32, terrain_height: i32) -> i32 {
+        let max_offset = self.max_offset();
+        ((terrain_height - max_offset)..=(terrain_height + max_offset))
+            .rev()
+            .find(|y| self.is_solid(glam::ivec3(x, *y, z), terrain_height))
+            .unwrap_or(terrain_height - max_offset)
+    }
+}
+
 /// Generates a chunk of voxels using the given world generation options.
-pub fn generate_chunk(options: WorldGenerationOptions, grid_position: glam::IVec3) -> Chunk {
-    let noise_module = options.as_noise_module();
+///
+/// Solid voxels are layered by their depth below the first air voxel above them, so that overhangs
+/// are covered by the surface voxels of their biome like the rest of the terrain. Caves are carved
+/// afterwards, and don't affect the layering. Ore veins are then placed in the remaining stone,
+/// structures from `structures` on the terrain, and trees and vegetation grown on the grass left
+/// uncovered.
+///
+/// Returns the chunk, along with the voxels of the features that reach into neighbouring chunks.
+pub fn generate_chunk(
+    options: WorldGenerationOptions,
+    grid_position: glam::IVec3,
+    registry: &VoxelRegistry,
+    structures: &structures::StructureLibrary,
+) -> (Chunk, features::PendingWrites) {
+    let sampler = TerrainSampler::new(&options);
+    let voxels = TerrainVoxels::new(&options, registry);
 
     let mut chunk = Chunk::new(grid_position, options.chunk_size);
-    let world_position = (grid_position * options.chunk_size.as_ivec3()).as_dvec3();
+    let chunk_origin = grid_position * options.chunk_size.as_ivec3();
+    let chunk_top = chunk_origin.y + options.chunk_size.y as i32 - 1;
+    let mut grass = Vec::new();
 
     for x in 0..options.chunk_size.x {
         for z in 0..options.chunk_size.z {
-            let position = world_position + glam::dvec3(x as f64, 0.0, z as f64);
-            let terrain_height = noise_module
-                .get([position.x, position.z])
-                .remap(-1.0, 1.0, 0.0, options.max_height as f64)
-                .floor() as i32;
-
-            for y in 0..options.chunk_size.y as i32 {
-                let global_y = options.chunk_size.y as i32 * grid_position.y + y;
-                let position = glam::uvec3(x, y as u32, z);
-
-                if global_y == terrain_height {
-                    chunk.set_voxel(
-                        position,
-                        if global_y <= options.sea_level_voxels() {
-                            Voxel::Sand
-                        } else {
-                            Voxel::Grass
-                        },
-                    );
-                } else if global_y
-                    >= terrain_height.saturating_sub(options.dirt_layer_thickness as i32)
-                    && global_y < terrain_height
-                {
-                    chunk.set_voxel(
-                        position,
-                        if global_y <= options.sea_level_voxels() {
-                            Voxel::Sand
-                        } else {
-                            Voxel::Dirt
-                        },
-                    )
-                } else if global_y < terrain_height {
-                    chunk.set_voxel(position, Voxel::Stone)
-                } else if global_y <= options.sea_level_voxels() {
-                    chunk.set_voxel(position, Voxel::Water)
+            let column = chunk_origin + glam::uvec3(x, 0, z).as_ivec3();
+            let terrain_height = sampler.terrain_height(column.x as f64, column.z as f64);
+            let climate = sampler.climate(column.x as f64, column.z as f64, terrain_height);
+
+            // Scan from far enough above the chunk that the depth of every solid voxel within the
+            // chunk is known, at least up to the thickness of the dirt layer and the ocean floor
+            let scan_top = chunk_top
+                + options
+                    .dirt_layer_thickness
+                    .max(options.cave_options.ocean_floor_thickness) as i32
+                + 1;
+            let mut depth: Option<u32> = None;
+            let mut underwater = false;
+            for y in (chunk_origin.y..=scan_top).rev() {
+                let position = glam::ivec3(column.x, y, column.z);
+                let is_water_level = y <= options.sea_level_voxels();
+
+                if !sampler.is_solid(position, terrain_height) {
+                    depth = None;
+                    if y <= chunk_top && is_water_level {
+                        chunk.set_voxel(
+                            glam::uvec3(x, (y - chunk_origin.y) as u32, z),
+                            voxels.water,
+                        );
+                    }
+                    continue;
+                }
+
+                let solid_depth = match depth {
+                    Some(depth) => depth + 1,
+                    None => {
+                        underwater = y < options.sea_level_voxels();
+                        0
+                    }
+                };
+                depth = Some(solid_depth);
+
+                if y > chunk_top {
+                    continue;
+                }
+
+                let voxel = if !sampler.is_cave(position, solid_depth, underwater) {
+                    voxels.layer_voxel(&options, climate, solid_depth, y)
+                } else if options.cave_options.flooded && is_water_level {
+                    voxels.water
+                } else {
+                    continue;
+                };
+                chunk.set_voxel(glam::uvec3(x, (y - chunk_origin.y) as u32, z), voxel);
+
+                if voxel == voxels.grass {
+                    grass.push((position, climate.biome));
                 }
             }
         }
     }
 
-    chunk
+    options
+        .ore_options
+        .place_ores(&options, &mut chunk, voxels.stone, &voxels.ores);
+    options.structure_options.place_structures(
+        &options,
+        structures,
+        |x, z| sampler.terrain_height(x as f64, z as f64),
+        &mut chunk,
+    );
+
+    // Structures may have covered or removed some of the grass
+    grass.retain(|(position, _)| {
+        let local = (*position - chunk_origin).as_uvec3();
+        let above = local + glam::UVec3::Y;
+        chunk.get_voxel(local) == Some(&voxels.grass)
+            && chunk.get_voxel(above).is_none_or(|voxel| voxel.is_air())
+    });
+    let pending_writes =
+        options
+            .feature_options
+            .place_features(&options, &mut chunk, &grass, &voxels.features);
+
+    (chunk, pending_writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{light::Light, registry::VoxelDefinition};
+
+    #[test]
+    fn fingerprint_covers_every_option() {
+        let options = WorldGenerationOptions::default();
+        assert_eq!(
+            options.fingerprint(),
+            WorldGenerationOptions::default().fingerprint()
+        );
+
+        // Options other than the seed change the world as well
+        let mut caves = options;
+        caves.cave_options.cheese_threshold += 0.1;
+        let mut ores = options;
+        ores.ore_options.coal.vein_size += 1;
+        for changed in [caves, ores] {
+            assert_eq!(changed.seed, options.seed);
+            assert_ne!(changed.fingerprint(), options.fingerprint());
+        }
+    }
+
+    #[test]
+    fn generated_voxels_cover_every_voxel_placed() {
+        let definitions = GENERATED_VOXELS
+            .iter()
+            .map(|name| VoxelDefinition {
+                name: name.to_string(),
+                color: [1.0; 4],
+                solid: true,
+                liquid: false,
+                opacity: Light::MAX_LEVEL,
+                emission: 0,
+            })
+            .collect();
+        let registry = VoxelRegistry::new(definitions).unwrap();
+
+        // Panics if any voxel the generator places is missing
+        TerrainVoxels::new(&WorldGenerationOptions::default(), &registry);
+    }
+
+    #[test]
+    fn zero_density_amplitude_generates_a_heightfield() {
+        let registry = VoxelRegistry::default();
+        let mut options = WorldGenerationOptions {
+            chunk_size: glam::UVec3::splat(8),
+            ..Default::default()
+        };
+        options.feature_options = features::FeatureOptions {
+            tree_chance: 0.0,
+            forest_tree_chance: 0.0,
+            bush_chance: 0.0,
+            tall_grass_chance: 0.0,
+            ..Default::default()
+        };
+        options.density_options.amplitude = 0.0;
+        options.cave_options.spaghetti_thickness = 0.0;
+        options.cave_options.cheese_threshold = 2.0;
+        let voxels = TerrainVoxels::new(&options, &registry);
+
+        let sampler = TerrainSampler::new(&options);
+        let terrain_height = sampler.terrain_height(0.0, 0.0);
+        let grid_position = glam::ivec3(0, terrain_height.div_euclid(8), 0);
+        let (chunk, _) = generate_chunk(
+            options,
+            grid_position,
+            &registry,
+            &structures::StructureLibrary::default(),
+        );
+
+        for x in 0..8 {
+            for z in 0..8 {
+                let terrain_height = sampler.terrain_height(x as f64, z as f64);
+                let climate = sampler.climate(x as f64, z as f64, terrain_height);
+                for y in 0..8 {
+                    let global_y = grid_position.y * 8 + y as i32;
+                    let voxel = *chunk.get_voxel(glam::uvec3(x, y, z)).unwrap();
+
+                    if global_y <= terrain_height {
+                        let depth = (terrain_height - global_y) as u32;
+                        assert_eq!(
+                            voxel,
+                            voxels.layer_voxel(&options, climate, depth, global_y)
+                        );
+                    } else if global_y <= options.sea_level_voxels() {
+                        assert_eq!(voxel, voxels.water);
+                    } else {
+                        assert_eq!(voxel, Voxel::AIR);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overhangs_are_covered_by_surface_voxels() {
+        let registry = VoxelRegistry::default();
+        let mut options = WorldGenerationOptions {
+            chunk_size: glam::UVec3::splat(16),
+            ..Default::default()
+        };
+        options.feature_options = features::FeatureOptions {
+            tree_chance: 0.0,
+            forest_tree_chance: 0.0,
+            bush_chance: 0.0,
+            tall_grass_chance: 0.0,
+            ..Default::default()
+        };
+        // Strong, high frequency density noise, so that the sampled chunk has overhangs
+        options.density_options.amplitude = 16.0;
+        options.density_options.frequency = 0.1;
+        options.cave_options.spaghetti_thickness = 0.0;
+        options.cave_options.cheese_threshold = 2.0;
+        let voxels = TerrainVoxels::new(&options, &registry);
+
+        // Sample a chunk on land, as the sea fills the overhangs below it
+        let noise_module = options.as_noise_module();
+        let (x, terrain_height) = (0..256)
+            .map(|x| {
+                (
+                    x * 16,
+                    options.terrain_height(&noise_module, x as f64 * 16.0, 0.0),
+                )
+            })
+            .find(|(_, height)| *height > options.sea_level_voxels() + 16)
+            .expect("to find land");
+        let grid_position = glam::ivec3(x / 16, terrain_height.div_euclid(16), 0);
+        let (chunk, _) = generate_chunk(
+            options,
+            grid_position,
+            &registry,
+            &structures::StructureLibrary::default(),
+        );
+
+        // Every solid voxel directly below air is a surface voxel
+        let mut overhangs = 0;
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 0..15 {
+                    let voxel = *chunk.get_voxel(glam::uvec3(x, y, z)).unwrap();
+                    let above = *chunk.get_voxel(glam::uvec3(x, y + 1, z)).unwrap();
+                    if voxel.is_air() && registry.is_solid(above) {
+                        overhangs += 1;
+                    }
+                    if registry.is_solid(voxel) && !registry.is_solid(above) {
+                        let is_surface_voxel = voxels.biomes.iter().any(|biome| biome.top == voxel)
+                            || voxel == voxels.snow
+                            || voxel == voxels.sand;
+                        assert!(
+                            is_surface_voxel,
+                            "solid voxel below air at {x} {y} {z} is not a surface voxel"
+                        );
+                    }
+                }
+            }
+        }
+        assert!(overhangs > 0, "no overhangs in the sampled chunk");
+    }
+
+    #[test]
+    fn caves_do_not_breach_the_ocean_floor() {
+        let registry = VoxelRegistry::default();
+        let mut options = WorldGenerationOptions {
+            chunk_size: glam::UVec3::splat(16),
+            ..Default::default()
+        };
+        options.density_options.amplitude = 0.0;
+        // Carve every voxel that isn't protected
+        options.cave_options.cheese_threshold = -2.0;
+
+        let noise_module = options.as_noise_module();
+        let thickness = options.cave_options.ocean_floor_thickness as i32;
+        let mut chunks = std::collections::HashMap::new();
+        let mut ocean_columns = 0;
+        for x in 0..16 {
+            for z in 0..16 {
+                let terrain_height = options.terrain_height(&noise_module, x as f64, z as f64);
+                if terrain_height >= options.sea_level_voxels() {
+                    continue;
+                }
+                ocean_columns += 1;
+
+                let grid_position = glam::ivec3(0, terrain_height.div_euclid(16), 0);
+                let chunk = chunks.entry(grid_position).or_insert_with(|| {
+                    generate_chunk(
+                        options,
+                        grid_position,
+                        &registry,
+                        &structures::StructureLibrary::default(),
+                    )
+                    .0
+                });
+                let local_y = terrain_height.rem_euclid(16);
+
+                for y in (local_y - thickness + 1).max(0)..=local_y {
+                    let voxel = *chunk.get_voxel(glam::uvec3(x, y as u32, z)).unwrap();
+                    assert!(
+                        registry.is_solid(voxel),
+                        "ocean floor carved at {x} {y} {z}"
+                    );
+                }
+                if local_y - thickness >= 0 {
+                    let voxel = *chunk
+                        .get_voxel(glam::uvec3(x, (local_y - thickness) as u32, z))
+                        .unwrap();
+                    assert!(voxel.is_air(), "cave below the ocean floor is not carved");
+                }
+            }
+        }
+
+        assert!(ocean_columns > 0, "expected the test area to contain ocean");
+    }
 }
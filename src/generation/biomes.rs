@@ -0,0 +1,205 @@
+use noise::{Add, Fbm, MultiFractal, NoiseFn, Perlin, ScaleBias};
+use serde::{Deserialize, Serialize};
+
+use super::WorldGenerationOptions;
+
+/// Region of the world with its own surface materials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    /// Sandy shore just above the sea level.
+    Beach,
+    /// Hot and dry sand dunes.
+    Desert,
+    /// Temperate grass plains.
+    Grassland,
+    /// Temperate and humid land.
+    Forest,
+    /// Cold, snow-covered land.
+    Tundra,
+    /// Bare rock high up in the mountains.
+    Alpine,
+}
+
+impl Biome {
+    /// Every biome.
+    pub const ALL: [Biome; 6] = [
+        Biome::Beach,
+        Biome::Desert,
+        Biome::Grassland,
+        Biome::Forest,
+        Biome::Tundra,
+        Biome::Alpine,
+    ];
+
+    /// Returns the names of the voxels covering the surface of the biome, and filling the layer
+    /// below it.
+    pub fn materials(self) -> (&'static str, &'static str) {
+        match self {
+            Biome::Beach | Biome::Desert => ("sand", "sand"),
+            Biome::Grassland | Biome::Forest => ("grass", "dirt"),
+            Biome::Tundra => ("snow", "dirt"),
+            Biome::Alpine => ("stone", "stone"),
+        }
+    }
+}
+
+/// Options for placing biomes.
+///
+/// Biomes are picked from temperature and humidity noise, with the temperature dropping with
+/// altitude. Both are offset by a small high frequency noise, so that biomes interleave at their
+/// borders instead of meeting in a sharp line.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BiomeOptions {
+    /// Frequency of the temperature noise.
+    pub temperature_frequency: f64,
+    /// Frequency of the humidity noise.
+    pub humidity_frequency: f64,
+    /// Strength of the noise blending biomes at their borders.
+    pub border_blend: f64,
+    /// How much the temperature drops from the sea level to the maximum height of the world.
+    pub altitude_cooling: f64,
+    /// Height above the sea level, in voxels, up to which the shore is a beach.
+    pub beach_height: u32,
+    /// Altitude above which mountains are bare rock, from 0 at the sea level to 1 at the maximum
+    /// height of the world.
+    pub alpine_altitude: f64,
+    /// Altitude above which the surface is covered by snow, from 0 at the sea level to 1 at the
+    /// maximum height of the world.
+    pub snow_altitude: f64,
+}
+
+impl Default for BiomeOptions {
+    fn default() -> Self {
+        Self {
+            temperature_frequency: 0.0015,
+            humidity_frequency: 0.002,
+            border_blend: 0.08,
+            altitude_cooling: 0.8,
+            beach_height: 2,
+            alpine_altitude: 0.55,
+            snow_altitude: 0.7,
+        }
+    }
+}
+
+impl BiomeOptions {
+    /// Creates a noise module for the temperature, from -1 (cold) to 1 (hot), before cooling by
+    /// altitude.
+    pub fn temperature_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
+        self.climate_noise_module(world.seed + 400, self.temperature_frequency)
+    }
+
+    /// Creates a noise module for the humidity, from -1 (dry) to 1 (humid).
+    pub fn humidity_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
+        self.climate_noise_module(world.seed + 410, self.humidity_frequency)
+    }
+
+    fn climate_noise_module(&self, seed: u32, frequency: f64) -> impl NoiseFn<f64, 2> {
+        let base = Fbm::<Perlin>::new(seed)
+            .set_frequency(frequency)
+            .set_persistence(0.5)
+            .set_octaves(3);
+
+        let border = Fbm::<Perlin>::new(seed + 1)
+            .set_frequency(frequency * 32.0)
+            .set_octaves(2);
+        let border = ScaleBias::new(border).set_scale(self.border_blend);
+
+        Add::new(base, border)
+    }
+
+    /// Returns the altitude of the given height, from 0 at the sea level to 1 at the maximum height
+    /// of the world.
+    fn altitude(&self, world: &WorldGenerationOptions, height: i32) -> f64 {
+        let sea_level = world.sea_level_voxels();
+        let land_height = (world.max_height as i32 - sea_level).max(1);
+        ((height - sea_level) as f64 / land_height as f64).clamp(0.0, 1.0)
+    }
+
+    /// Returns the biome of a column whose surface is at the given height.
+    pub fn biome(
+        &self,
+        world: &WorldGenerationOptions,
+        temperature: f64,
+        humidity: f64,
+        height: i32,
+    ) -> Biome {
+        let altitude = self.altitude(world, height);
+        let temperature = temperature - altitude * self.altitude_cooling;
+
+        if height <= world.sea_level_voxels() + self.beach_height as i32 {
+            Biome::Beach
+        } else if altitude >= self.alpine_altitude {
+            Biome::Alpine
+        } else if temperature < -0.4 {
+            Biome::Tundra
+        } else if temperature > 0.35 && humidity < 0.0 {
+            Biome::Desert
+        } else if humidity > 0.1 {
+            Biome::Forest
+        } else {
+            Biome::Grassland
+        }
+    }
+
+    /// Returns whether the surface at the given height is covered by snow.
+    ///
+    /// The snow line is lower where the climate is colder, which also makes it follow the ragged
+    /// biome borders.
+    pub fn is_snow_capped(
+        &self,
+        world: &WorldGenerationOptions,
+        temperature: f64,
+        height: i32,
+    ) -> bool {
+        self.altitude(world, height) >= self.snow_altitude + temperature * 0.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn biomes_follow_altitude_and_climate() {
+        let world = WorldGenerationOptions::default();
+        let options = BiomeOptions::default();
+        let sea_level = world.sea_level_voxels();
+
+        assert_eq!(options.biome(&world, 0.0, 0.0, sea_level + 1), Biome::Beach);
+        assert_eq!(
+            options.biome(&world, 1.0, 1.0, world.max_height as i32),
+            Biome::Alpine
+        );
+        assert_eq!(
+            options.biome(&world, -1.0, 0.0, sea_level + 10),
+            Biome::Tundra
+        );
+        assert_eq!(
+            options.biome(&world, 1.0, -1.0, sea_level + 10),
+            Biome::Desert
+        );
+        assert_eq!(
+            options.biome(&world, 0.0, 1.0, sea_level + 10),
+            Biome::Forest
+        );
+        assert_eq!(
+            options.biome(&world, 0.0, 0.0, sea_level + 10),
+            Biome::Grassland
+        );
+    }
+
+    #[test]
+    fn snow_line_is_lower_in_cold_climates() {
+        let world = WorldGenerationOptions::default();
+        let options = BiomeOptions::default();
+        let sea_level = world.sea_level_voxels();
+        let land_height = world.max_height as i32 - sea_level;
+        let height = sea_level + (land_height as f64 * options.snow_altitude) as i32;
+
+        assert!(options.is_snow_capped(&world, -1.0, height));
+        assert!(!options.is_snow_capped(&world, 1.0, height));
+        assert!(options.is_snow_capped(&world, 1.0, world.max_height as i32));
+    }
+}
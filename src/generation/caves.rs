@@ -0,0 +1,69 @@
+use noise::{Abs, Fbm, Max, MultiFractal, NoiseFn, Perlin, ScaleBias, ScalePoint};
+use serde::{Deserialize, Serialize};
+
+use super::WorldGenerationOptions;
+
+/// Options for carving caves into the terrain.
+///
+/// Spaghetti caves are long, winding tunnels following the intersection of two noise fields.
+/// Cheese caves are large open caverns where a single noise field exceeds a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaveOptions {
+    /// Frequency of the spaghetti caves.
+    pub spaghetti_frequency: f64,
+    /// Thickness of the spaghetti caves. A value of zero disables them.
+    pub spaghetti_thickness: f64,
+    /// Frequency of the cheese caves.
+    pub cheese_frequency: f64,
+    /// Noise value above which cheese caves are carved. Values of one or more disable them.
+    pub cheese_threshold: f64,
+    /// Thickness, in voxels, of the ocean floor that caves never carve into, so that the ocean
+    /// doesn't drain into them.
+    pub ocean_floor_thickness: u32,
+    /// Whether caves below the sea level are filled with water.
+    pub flooded: bool,
+}
+
+impl Default for CaveOptions {
+    fn default() -> Self {
+        Self {
+            spaghetti_frequency: 0.015,
+            spaghetti_thickness: 0.06,
+            cheese_frequency: 0.01,
+            cheese_threshold: 0.55,
+            ocean_floor_thickness: 4,
+            flooded: false,
+        }
+    }
+}
+
+impl CaveOptions {
+    /// Creates a noise module whose value is positive wherever a cave is carved.
+    pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 3> {
+        let tunnel_noise = |seed| {
+            let perlin = Perlin::new(seed);
+            let scaled = ScalePoint::new(perlin)
+                .set_scale(self.spaghetti_frequency)
+                .set_y_scale(self.spaghetti_frequency * 2.0);
+
+            Abs::new(scaled)
+        };
+
+        // Tunnels follow the lines where both noise fields are close to zero
+        let spaghetti = ScaleBias::new(Max::new(
+            tunnel_noise(world.seed + 300),
+            tunnel_noise(world.seed + 301),
+        ))
+        .set_scale(-1.0)
+        .set_bias(self.spaghetti_thickness);
+
+        let cheese = Fbm::<Perlin>::new(world.seed + 302)
+            .set_frequency(self.cheese_frequency)
+            .set_persistence(0.5)
+            .set_octaves(3);
+        let cheese = ScaleBias::new(cheese).set_bias(-self.cheese_threshold);
+
+        Max::new(spaghetti, cheese)
+    }
+}
@@ -0,0 +1,47 @@
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin, ScalePoint};
+use serde::{Deserialize, Serialize};
+
+use super::WorldGenerationOptions;
+
+/// Options for the 3D density noise that carves overhangs and arches into the terrain.
+///
+/// The density of a voxel is its depth below the 2D terrain height, offset by the 3D noise scaled
+/// by `amplitude`. Voxels with a non-negative density are solid.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DensityOptions {
+    /// Largest distance, in voxels, the 3D noise moves the terrain surface up or down. A value of
+    /// zero disables the 3D noise, leaving a pure heightfield.
+    pub amplitude: f64,
+    /// Frequency of the 3D noise.
+    pub frequency: f64,
+    /// Scaling of the vertical axis of the 3D noise. Values above one produce thinner, more
+    /// horizontal overhangs.
+    pub vertical_scale: f64,
+    /// Number of octaves of the 3D noise.
+    pub octaves: usize,
+}
+
+impl Default for DensityOptions {
+    fn default() -> Self {
+        Self {
+            amplitude: 8.0,
+            frequency: 0.03,
+            vertical_scale: 1.5,
+            octaves: 3,
+        }
+    }
+}
+
+impl DensityOptions {
+    /// Creates a noise module that offsets the terrain density, with values roughly between -1 and
+    /// 1.
+    pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 3> {
+        let fbm = Fbm::<Perlin>::new(world.seed + 200)
+            .set_frequency(self.frequency)
+            .set_persistence(0.5)
+            .set_octaves(self.octaves.clamp(1, Fbm::<Perlin>::MAX_OCTAVES));
+
+        ScalePoint::new(fbm).set_y_scale(self.vertical_scale)
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{Chunk, Voxel};
+
+use super::{biomes::Biome, position_rng, WorldGenerationOptions};
+
+/// Options for growing trees and vegetation on grass.
+///
+/// Every grass voxel on the surface grows at most one feature, picked from the seed and its
+/// position. Features may reach into neighbouring chunks, see [`PendingWrites`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureOptions {
+    /// Chance of a tree growing on a grass voxel in forests.
+    pub forest_tree_chance: f64,
+    /// Chance of a tree growing on a grass voxel outside of forests.
+    pub tree_chance: f64,
+    /// Chance of a bush growing on a grass voxel.
+    pub bush_chance: f64,
+    /// Chance of tall grass growing on a grass voxel.
+    pub tall_grass_chance: f64,
+    /// Smallest height of tree trunks, in voxels.
+    pub min_trunk_height: u32,
+    /// Largest height of tree trunks, in voxels.
+    pub max_trunk_height: u32,
+    /// Radius of the leaf crown of trees, in voxels.
+    pub leaf_radius: u32,
+}
+
+impl Default for FeatureOptions {
+    fn default() -> Self {
+        Self {
+            forest_tree_chance: 0.03,
+            tree_chance: 0.002,
+            bush_chance: 0.01,
+            tall_grass_chance: 0.08,
+            min_trunk_height: 4,
+            max_trunk_height: 7,
+            leaf_radius: 2,
+        }
+    }
+}
+
+/// Voxels placed by features, resolved from a voxel registry.
+pub(super) struct FeatureVoxels {
+    pub(super) wood: Voxel,
+    pub(super) leaves: Voxel,
+    pub(super) tall_grass: Voxel,
+}
+
+/// Voxels written by features outside of the chunk that placed them, grouped by the grid position
+/// of the chunk they fall into, and then of the chunk that placed them.
+///
+/// Writes are kept for as long as the chunk that placed them is loaded, so that chunks unloaded and
+/// loaded again in the meantime receive them again. Writes only ever replace air. Chunks also
+/// remember which of their neighbours' features they received, and never receive them twice, so
+/// that features removed from a saved chunk don't grow back. Applying the same writes twice, or to
+/// a chunk that already received them, changes nothing.
+#[derive(Debug, Default)]
+pub struct PendingWrites {
+    writes: HashMap<glam::IVec3, HashMap<glam::IVec3, Vec<(glam::UVec3, Voxel)>>>,
+}
+
+impl PendingWrites {
+    /// Returns whether there are no pending writes.
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Queues a write of `voxel` at the given position in the world, placed by the chunk at the
+    /// grid position `source`.
+    pub fn push(
+        &mut self,
+        chunk_size: glam::UVec3,
+        source: glam::IVec3,
+        position: glam::IVec3,
+        voxel: Voxel,
+    ) {
+        let chunk_size = chunk_size.as_ivec3();
+        let grid_position = position.div_euclid(chunk_size);
+        let local_position = position.rem_euclid(chunk_size).as_uvec3();
+
+        self.writes
+            .entry(grid_position)
+            .or_default()
+            .entry(source)
+            .or_default()
+            .push((local_position, voxel));
+    }
+
+    /// Moves every write of `other` into these pending writes.
+    pub fn append(&mut self, other: PendingWrites) {
+        for (grid_position, sources) in other.writes {
+            let target = self.writes.entry(grid_position).or_default();
+            for (source, writes) in sources {
+                target.entry(source).or_default().extend(writes);
+            }
+        }
+    }
+
+    /// Writes the voxels queued for the chunk at the given grid position into `chunk`, unless it
+    /// already received the features of the chunk that placed them.
+    ///
+    /// Returns the positions within the chunk of the voxels that changed.
+    pub fn apply(&self, chunk: &mut Chunk) -> Vec<glam::UVec3> {
+        let Some(sources) = self.writes.get(&chunk.grid_position) else {
+            return Vec::new();
+        };
+
+        let mut received_features = chunk.received_features();
+        let mut changed = Vec::new();
+        for (&source, writes) in sources {
+            // Features reaching farther than the direct neighbours are written every time
+            if let Some(bit) = neighbour_bit(source - chunk.grid_position) {
+                if received_features & bit != 0 {
+                    continue;
+                }
+                received_features |= bit;
+            }
+
+            for &(position, voxel) in writes {
+                if chunk
+                    .get_voxel(position)
+                    .is_some_and(|current| current.is_air())
+                {
+                    chunk.set_voxel(position, voxel);
+                    changed.push(position);
+                }
+            }
+        }
+        chunk.set_received_features(received_features);
+
+        changed
+    }
+
+    /// Returns the grid positions of the chunks with pending writes.
+    pub fn grid_positions(&self) -> impl Iterator<Item = glam::IVec3> + '_ {
+        self.writes.keys().copied()
+    }
+
+    /// Returns the writes placed by the chunk at the given grid position, along with the grid
+    /// position of the chunk they fall into.
+    pub fn placed_by(
+        &self,
+        source: glam::IVec3,
+    ) -> impl Iterator<Item = (glam::IVec3, &[(glam::UVec3, Voxel)])> + '_ {
+        self.writes
+            .iter()
+            .filter_map(move |(grid_position, sources)| {
+                let writes = sources.get(&source)?;
+                Some((*grid_position, writes.as_slice()))
+            })
+    }
+
+    /// Removes the writes placed by the chunk at the given grid position.
+    pub fn remove_source(&mut self, source: glam::IVec3) {
+        self.writes.retain(|_, sources| {
+            sources.remove(&source);
+            !sources.is_empty()
+        });
+    }
+
+    /// Returns an estimate of the memory used by the pending writes, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.writes
+            .values()
+            .flat_map(|sources| sources.values())
+            .map(|writes| {
+                std::mem::size_of::<(glam::IVec3, Vec<(glam::UVec3, Voxel)>)>()
+                    + writes.capacity() * std::mem::size_of::<(glam::UVec3, Voxel)>()
+            })
+            .sum()
+    }
+
+    /// Removes every pending write.
+    pub fn clear(&mut self) {
+        self.writes.clear();
+    }
+}
+
+/// Returns the bit of the neighbour at the given offset from a chunk in
+/// [`Chunk::received_features`], or `None` if the offset is not a direct neighbour.
+fn neighbour_bit(offset: glam::IVec3) -> Option<u32> {
+    if offset.abs().max_element() > 1 {
+        return None;
+    }
+
+    let index = offset + glam::IVec3::ONE;
+    Some(1 << (index.x + index.y * 3 + index.z * 9))
+}
+
+/// Writes the voxels of features into a chunk, queuing the voxels outside of it.
+struct FeatureWriter<'a> {
+    chunk: &'a mut Chunk,
+    pending: PendingWrites,
+}
+
+impl FeatureWriter<'_> {
+    /// Writes `voxel` at the given position in the world, if it is air.
+    fn write(&mut self, position: glam::IVec3, voxel: Voxel) {
+        let size = self.chunk.size();
+        let local_position = position - self.chunk.grid_position * size.as_ivec3();
+        if local_position.cmplt(glam::IVec3::ZERO).any()
+            || local_position.cmpge(size.as_ivec3()).any()
+        {
+            self.pending
+                .push(size, self.chunk.grid_position, position, voxel);
+            return;
+        }
+
+        let local_position = local_position.as_uvec3();
+        if self
+            .chunk
+            .get_voxel(local_position)
+            .is_some_and(|current| current.is_air())
+        {
+            self.chunk.set_voxel(local_position, voxel);
+        }
+    }
+}
+
+impl FeatureOptions {
+    /// Grows features on the given grass voxels of the chunk, along with the biome of their column.
+    ///
+    /// Returns the voxels of the features that fall outside of the chunk.
+    pub(super) fn place_features(
+        &self,
+        world: &WorldGenerationOptions,
+        chunk: &mut Chunk,
+        grass: &[(glam::IVec3, Biome)],
+        voxels: &FeatureVoxels,
+    ) -> PendingWrites {
+        let mut writer = FeatureWriter {
+            chunk,
+            pending: PendingWrites::default(),
+        };
+
+        for &(position, biome) in grass {
+            let mut rng = position_rng(world, 600, position);
+            let tree_chance = if biome == Biome::Forest {
+                self.forest_tree_chance
+            } else {
+                self.tree_chance
+            };
+
+            let roll = rng.gen::<f64>();
+            let base = position + glam::IVec3::Y;
+            if roll < tree_chance {
+                self.grow_tree(&mut writer, &mut rng, base, voxels);
+            } else if roll < tree_chance + self.bush_chance {
+                Self::grow_bush(&mut writer, &mut rng, base, voxels);
+            } else if roll < tree_chance + self.bush_chance + self.tall_grass_chance {
+                writer.write(base, voxels.tall_grass);
+            }
+        }
+
+        writer.pending
+    }
+
+    /// Grows a tree whose trunk starts at `base`.
+    fn grow_tree(
+        &self,
+        writer: &mut FeatureWriter,
+        rng: &mut StdRng,
+        base: glam::IVec3,
+        voxels: &FeatureVoxels,
+    ) {
+        let min_height = self.min_trunk_height.max(1);
+        let trunk_height = rng.gen_range(min_height..=self.max_trunk_height.max(min_height)) as i32;
+        for y in 0..trunk_height {
+            writer.write(base + glam::ivec3(0, y, 0), voxels.wood);
+        }
+
+        // Round crown around the top of the trunk, with some leaves randomly missing at its edge
+        let radius = self.leaf_radius as i32;
+        let top = base + glam::ivec3(0, trunk_height - 1, 0);
+        for x in -radius..=radius {
+            for y in -radius..=radius {
+                for z in -radius..=radius {
+                    let offset = glam::ivec3(x, y, z);
+                    let distance_squared = offset.length_squared();
+                    if distance_squared > radius * radius + 1
+                        || (distance_squared >= radius * radius && rng.gen_bool(0.5))
+                    {
+                        continue;
+                    }
+
+                    writer.write(top + glam::IVec3::Y + offset, voxels.leaves);
+                }
+            }
+        }
+    }
+
+    /// Grows a small cluster of leaves at `base`.
+    fn grow_bush(
+        writer: &mut FeatureWriter,
+        rng: &mut StdRng,
+        base: glam::IVec3,
+        voxels: &FeatureVoxels,
+    ) {
+        writer.write(base, voxels.leaves);
+        for offset in [
+            glam::IVec3::X,
+            glam::IVec3::NEG_X,
+            glam::IVec3::Z,
+            glam::IVec3::NEG_Z,
+            glam::IVec3::Y,
+        ] {
+            if rng.gen_bool(0.5) {
+                writer.write(base + offset, voxels.leaves);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::registry::VoxelRegistry;
+
+    #[test]
+    fn trees_at_chunk_edges_write_into_neighbouring_chunks() {
+        let world = WorldGenerationOptions::default();
+        let options = FeatureOptions {
+            forest_tree_chance: 1.0,
+            ..Default::default()
+        };
+        let registry = VoxelRegistry::default();
+        let voxels = FeatureVoxels {
+            wood: registry.by_name("wood").unwrap(),
+            leaves: registry.by_name("leaves").unwrap(),
+            tall_grass: registry.by_name("tall_grass").unwrap(),
+        };
+
+        // A tree on the top corner of the chunk reaches into the chunks above and beside it
+        let size = glam::UVec3::splat(8);
+        let mut chunk = Chunk::new(glam::IVec3::ZERO, size);
+        let grass = [(glam::ivec3(7, 4, 7), Biome::Forest)];
+        let pending = options.place_features(&world, &mut chunk, &grass, &voxels);
+
+        assert_eq!(chunk.get_voxel(glam::uvec3(7, 5, 7)), Some(&voxels.wood));
+
+        let mut above = Chunk::new(glam::IVec3::Y, size);
+        assert!(!pending.apply(&mut above).is_empty());
+        assert!(!above.is_empty());
+        for grid_position in [glam::ivec3(1, 1, 0), glam::ivec3(0, 1, 1)] {
+            let mut neighbour = Chunk::new(grid_position, size);
+            assert!(
+                !pending.apply(&mut neighbour).is_empty(),
+                "no leaves written into {grid_position}"
+            );
+        }
+
+        // Chunks only receive the features of a neighbour once
+        assert!(pending.apply(&mut above).is_empty());
+    }
+
+    #[test]
+    fn features_are_only_received_once_from_each_neighbour() {
+        let registry = VoxelRegistry::default();
+        let leaves = registry.by_name("leaves").unwrap();
+        let size = glam::UVec3::splat(8);
+        let source = glam::IVec3::ZERO;
+        let writes = || {
+            let mut pending = PendingWrites::default();
+            pending.push(size, source, glam::ivec3(2, 8, 2), leaves);
+            pending.push(size, source, glam::ivec3(3, 8, 2), leaves);
+            pending
+        };
+
+        let mut above = Chunk::new(glam::IVec3::Y, size);
+        assert_eq!(writes().apply(&mut above).len(), 2);
+
+        // The neighbour is generated again after some of its leaves were removed from the chunk,
+        // which keeps them removed
+        above.set_voxel(glam::uvec3(2, 0, 2), Voxel::AIR);
+        assert!(writes().apply(&mut above).is_empty());
+        assert_eq!(above.get_voxel(glam::uvec3(2, 0, 2)), Some(&Voxel::AIR));
+
+        // Chunks generated from scratch receive the features again
+        let mut regenerated = Chunk::new(glam::IVec3::Y, size);
+        assert_eq!(writes().apply(&mut regenerated).len(), 2);
+
+        // Writes are dropped along with the chunk that placed them
+        let mut pending = writes();
+        pending.remove_source(source);
+        assert!(pending.is_empty());
+    }
+}
@@ -2,11 +2,13 @@ use noise::{
     Billow, Blend, Cache, Constant, Exponent, Fbm, MultiFractal, Multiply, NoiseFn, Perlin,
     RidgedMulti, ScaleBias, Seedable, Turbulence,
 };
+use serde::{Deserialize, Serialize};
 
 use super::WorldGenerationOptions;
 
 /// Options for generating hills.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HillOptions {
     /// Lacunarity of the hills generation.
     pub lacunarity: f64,
@@ -16,6 +18,16 @@ pub struct HillOptions {
     pub amount: f64,
 }
 
+impl Default for HillOptions {
+    fn default() -> Self {
+        Self {
+            lacunarity: 2.162109375,
+            twist: 1.0,
+            amount: 1.5 / 2.0,
+        }
+    }
+}
+
 impl HillOptions {
     /// Creates a noise module that defines the shape of the hills.
     pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
@@ -2,11 +2,13 @@ use noise::{
     Add, Blend, Cache, Constant, Exponent, Fbm, Max, MultiFractal, Multiply, NoiseFn, Perlin,
     RidgedMulti, ScaleBias, Seedable, Select, Turbulence,
 };
+use serde::{Deserialize, Serialize};
 
 use super::WorldGenerationOptions;
 
 /// Options for generating mountains.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MountainOptions {
     /// Lacunarity of the mountains generation.
     pub lacunarity: f64,
@@ -18,6 +20,17 @@ pub struct MountainOptions {
     pub amount: f64,
 }
 
+impl Default for MountainOptions {
+    fn default() -> Self {
+        Self {
+            lacunarity: 2.142578125,
+            twist: 0.5,
+            glaciation: 1.375,
+            amount: 0.5,
+        }
+    }
+}
+
 impl MountainOptions {
     /// Creates a noise module that defines the shape of the mountains.
     pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
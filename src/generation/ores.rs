@@ -0,0 +1,292 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{Chunk, Voxel};
+
+use super::{position_rng, WorldGenerationOptions};
+
+/// Options for the veins of a single kind of ore.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OreVeinOptions {
+    /// Lowest height, in voxels, at which veins start.
+    pub min_height: i32,
+    /// Highest height, in voxels, at which veins start.
+    pub max_height: i32,
+    /// Average number of veins in a volume of 16×16×16 voxels. A value of zero disables the ore.
+    pub frequency: f64,
+    /// Number of voxels in each vein.
+    pub vein_size: u32,
+}
+
+impl Default for OreVeinOptions {
+    fn default() -> Self {
+        Self {
+            min_height: 0,
+            max_height: 64,
+            frequency: 1.0,
+            vein_size: 8,
+        }
+    }
+}
+
+/// Options for placing ores in the stone below the surface.
+///
+/// Veins are random walks through the stone, which makes small veins compact blobs and large veins
+/// winding streaks. Veins are placed from the seed and the position of the chunk they start in, and
+/// may reach into neighbouring chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OreOptions {
+    /// Options for coal veins.
+    pub coal: OreVeinOptions,
+    /// Options for iron veins.
+    pub iron: OreVeinOptions,
+    /// Options for gold veins.
+    pub gold: OreVeinOptions,
+    /// Options for diamond veins.
+    pub diamond: OreVeinOptions,
+}
+
+impl Default for OreOptions {
+    fn default() -> Self {
+        Self {
+            coal: OreVeinOptions {
+                min_height: 0,
+                max_height: 112,
+                frequency: 1.5,
+                vein_size: 12,
+            },
+            iron: OreVeinOptions {
+                min_height: -32,
+                max_height: 80,
+                frequency: 1.0,
+                vein_size: 8,
+            },
+            gold: OreVeinOptions {
+                min_height: -64,
+                max_height: 40,
+                frequency: 0.3,
+                vein_size: 6,
+            },
+            diamond: OreVeinOptions {
+                min_height: -128,
+                max_height: 16,
+                frequency: 0.15,
+                vein_size: 4,
+            },
+        }
+    }
+}
+
+/// Volume, in voxels, that the frequency of veins is relative to.
+const FREQUENCY_VOLUME: f64 = 16.0 * 16.0 * 16.0;
+
+/// Directions a vein can grow in at every step.
+const VEIN_STEPS: [glam::IVec3; 6] = [
+    glam::IVec3::X,
+    glam::IVec3::NEG_X,
+    glam::IVec3::Y,
+    glam::IVec3::NEG_Y,
+    glam::IVec3::Z,
+    glam::IVec3::NEG_Z,
+];
+
+impl OreOptions {
+    /// Returns the name of the voxel of every ore, along with the options of its veins.
+    pub fn veins(&self) -> [(&'static str, OreVeinOptions); 4] {
+        [
+            ("coal_ore", self.coal),
+            ("iron_ore", self.iron),
+            ("gold_ore", self.gold),
+            ("diamond_ore", self.diamond),
+        ]
+    }
+
+    /// Replaces the `stone` voxels of the chunk that are part of an ore vein.
+    ///
+    /// `ores` holds the voxel of every ore, in the order of [`OreOptions::veins`]. Where veins of
+    /// different ores overlap, the first ore wins.
+    pub(super) fn place_ores(
+        &self,
+        world: &WorldGenerationOptions,
+        chunk: &mut Chunk,
+        stone: Voxel,
+        ores: &[Voxel],
+    ) {
+        let chunk_size = chunk.size().as_ivec3();
+        let chunk_origin = chunk.grid_position * chunk_size;
+
+        for (index, ((_, vein), ore)) in self.veins().iter().zip(ores).enumerate() {
+            if vein.frequency <= 0.0 || vein.vein_size == 0 {
+                continue;
+            }
+
+            // Veins never reach further than their size from the voxel they start at
+            let reach = vein.vein_size as i32 - 1;
+            let min_chunk = (chunk_origin - reach).div_euclid(chunk_size);
+            let max_chunk = (chunk_origin + chunk_size - 1 + reach).div_euclid(chunk_size);
+
+            for x in min_chunk.x..=max_chunk.x {
+                for y in min_chunk.y..=max_chunk.y {
+                    for z in min_chunk.z..=max_chunk.z {
+                        let origin_chunk = glam::ivec3(x, y, z);
+                        Self::for_each_vein_voxel(
+                            world,
+                            index,
+                            vein,
+                            origin_chunk,
+                            chunk_size,
+                            |position| {
+                                let local = position - chunk_origin;
+                                if local.cmplt(glam::IVec3::ZERO).any()
+                                    || local.cmpge(chunk_size).any()
+                                {
+                                    return;
+                                }
+
+                                let local = local.as_uvec3();
+                                if chunk.get_voxel(local) == Some(&stone) {
+                                    chunk.set_voxel(local, *ore);
+                                }
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calls `place` with the position of every voxel of the veins of an ore that start in the chunk
+    /// at `origin_chunk`.
+    fn for_each_vein_voxel(
+        world: &WorldGenerationOptions,
+        ore_index: usize,
+        vein: &OreVeinOptions,
+        origin_chunk: glam::IVec3,
+        chunk_size: glam::IVec3,
+        mut place: impl FnMut(glam::IVec3),
+    ) {
+        let chunk_origin = origin_chunk * chunk_size;
+        let chunk_top = chunk_origin.y + chunk_size.y - 1;
+        if chunk_top < vein.min_height || chunk_origin.y > vein.max_height {
+            return;
+        }
+
+        let mut rng = position_rng(world, 500 + ore_index as u32, origin_chunk);
+        let expected = vein.frequency * chunk_size.as_dvec3().element_product() / FREQUENCY_VOLUME;
+        let count = expected.floor() as u32 + rng.gen_bool(expected.fract()) as u32;
+
+        for _ in 0..count {
+            let mut position = chunk_origin
+                + glam::ivec3(
+                    rng.gen_range(0..chunk_size.x),
+                    rng.gen_range(0..chunk_size.y),
+                    rng.gen_range(0..chunk_size.z),
+                );
+            if !(vein.min_height..=vein.max_height).contains(&position.y) {
+                continue;
+            }
+
+            for _ in 0..vein.vein_size {
+                place(position);
+                position += VEIN_STEPS[rng.gen_range(0..VEIN_STEPS.len())];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::registry::VoxelRegistry;
+
+    fn stone_chunk(grid_position: glam::IVec3, size: glam::UVec3, stone: Voxel) -> Chunk {
+        let mut chunk = Chunk::new(grid_position, size);
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    chunk.set_voxel(glam::uvec3(x, y, z), stone);
+                }
+            }
+        }
+        chunk
+    }
+
+    #[test]
+    fn veins_continue_across_chunk_borders() {
+        let world = WorldGenerationOptions::default();
+        let size = glam::UVec3::splat(8);
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let ore = registry.by_name("coal_ore").unwrap();
+        let options = OreOptions {
+            coal: OreVeinOptions {
+                min_height: -64,
+                max_height: 64,
+                frequency: 8.0,
+                vein_size: 16,
+            },
+            iron: OreVeinOptions {
+                frequency: 0.0,
+                ..Default::default()
+            },
+            gold: OreVeinOptions {
+                frequency: 0.0,
+                ..Default::default()
+            },
+            diamond: OreVeinOptions {
+                frequency: 0.0,
+                ..Default::default()
+            },
+        };
+
+        let mut neighbour = stone_chunk(glam::IVec3::X, size, stone);
+        options.place_ores(&world, &mut neighbour, stone, &[ore]);
+
+        let mut crossing = 0;
+        OreOptions::for_each_vein_voxel(
+            &world,
+            0,
+            &options.coal,
+            glam::IVec3::ZERO,
+            size.as_ivec3(),
+            |position| {
+                let local = position - neighbour.grid_position * size.as_ivec3();
+                if local.cmpge(glam::IVec3::ZERO).all() && local.cmplt(size.as_ivec3()).all() {
+                    crossing += 1;
+                    assert_eq!(neighbour.get_voxel(local.as_uvec3()), Some(&ore));
+                }
+            },
+        );
+
+        assert!(crossing > 0, "expected a vein to cross the chunk border");
+    }
+
+    #[test]
+    fn ores_only_replace_stone_within_their_height_range() {
+        let world = WorldGenerationOptions::default();
+        let options = OreOptions::default();
+        let size = glam::UVec3::splat(16);
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let ores = options
+            .veins()
+            .map(|(name, _)| registry.by_name(name).unwrap());
+
+        let mut air = Chunk::new(glam::IVec3::ZERO, size);
+        options.place_ores(&world, &mut air, stone, &ores);
+        assert!(air.is_empty());
+
+        // No vein starts high enough to reach this chunk
+        let mut sky = stone_chunk(glam::ivec3(0, 16, 0), size, stone);
+        options.place_ores(&world, &mut sky, stone, &ores);
+        for x in 0..16 {
+            for y in 0..16 {
+                for z in 0..16 {
+                    assert_eq!(sky.get_voxel(glam::uvec3(x, y, z)), Some(&stone));
+                }
+            }
+        }
+    }
+}
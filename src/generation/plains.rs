@@ -1,14 +1,24 @@
 use noise::{Billow, Cache, MultiFractal, Multiply, NoiseFn, Perlin, ScaleBias};
+use serde::{Deserialize, Serialize};
 
 use super::WorldGenerationOptions;
 
 /// Options for generating plains.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlainOptions {
     /// Lacunarity of the plains generation.
     pub lacunarity: f64,
 }
 
+impl Default for PlainOptions {
+    fn default() -> Self {
+        Self {
+            lacunarity: 2.314453125,
+        }
+    }
+}
+
 impl PlainOptions {
     /// Creates a noise module that defines the shape of the plains.
     pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
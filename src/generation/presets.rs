@@ -0,0 +1,185 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use super::WorldGenerationOptions;
+
+/// Extension of preset files.
+const PRESET_EXTENSION: &str = "ron";
+
+/// Errors that can occur while loading or saving a preset.
+#[derive(Debug)]
+pub enum PresetError {
+    /// The preset file could not be read or written.
+    Io(io::Error),
+    /// The preset file is not valid.
+    Parse(ron::error::SpannedError),
+    /// The options could not be serialized.
+    Serialize(ron::Error),
+    /// The preset name is empty or contains path separators.
+    InvalidName(String),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to access preset: {error}"),
+            Self::Parse(error) => write!(f, "failed to parse preset: {error}"),
+            Self::Serialize(error) => write!(f, "failed to serialize preset: {error}"),
+            Self::InvalidName(name) => write!(f, "`{name}` is not a valid preset name"),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+/// Loads the preset file at the given path.
+///
+/// Options outside of their valid range are adjusted, see [`WorldGenerationOptions::sanitize`].
+pub fn load_preset_file(path: impl AsRef<Path>) -> Result<WorldGenerationOptions, PresetError> {
+    let source = std::fs::read_to_string(path).map_err(PresetError::Io)?;
+    let mut options: WorldGenerationOptions = ron::from_str(&source).map_err(PresetError::Parse)?;
+    options.sanitize();
+
+    Ok(options)
+}
+
+/// Directory of named world generation presets.
+///
+/// Every preset is stored as a RON file named after the preset. Options missing from a preset file
+/// take their default values, so presets keep loading when new options are added.
+#[derive(Debug, Clone)]
+pub struct PresetLibrary {
+    directory: PathBuf,
+}
+
+impl PresetLibrary {
+    /// Creates a library of the presets in the given directory.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Returns the names of every preset in the library, sorted alphabetically.
+    pub fn names(&self) -> Result<Vec<String>, PresetError> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(PresetError::Io(error)),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(PresetError::Io)?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == PRESET_EXTENSION)
+            {
+                if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// Loads the preset with the given name.
+    pub fn load(&self, name: &str) -> Result<WorldGenerationOptions, PresetError> {
+        load_preset_file(self.path(name)?)
+    }
+
+    /// Saves the given options as a preset, replacing any existing preset with the same name.
+    pub fn save(&self, name: &str, options: &WorldGenerationOptions) -> Result<(), PresetError> {
+        let path = self.path(name)?;
+        let source = ron::ser::to_string_pretty(options, ron::ser::PrettyConfig::default())
+            .map_err(PresetError::Serialize)?;
+
+        std::fs::create_dir_all(&self.directory).map_err(PresetError::Io)?;
+        std::fs::write(path, source).map_err(PresetError::Io)
+    }
+
+    /// Copies the preset with the given name to a new preset, returning the name of the copy.
+    pub fn duplicate(&self, name: &str) -> Result<String, PresetError> {
+        let options = self.load(name)?;
+
+        let mut copy_name = format!("{name} copy");
+        let mut copy_number = 2;
+        while self.path(&copy_name)?.exists() {
+            copy_name = format!("{name} copy {copy_number}");
+            copy_number += 1;
+        }
+
+        self.save(&copy_name, &options)?;
+        Ok(copy_name)
+    }
+
+    /// Returns the path of the preset file with the given name.
+    fn path(&self, name: &str) -> Result<PathBuf, PresetError> {
+        let is_valid = !name.trim().is_empty()
+            && Path::new(name)
+                .file_name()
+                .is_some_and(|file_name| file_name == name);
+        if !is_valid {
+            return Err(PresetError::InvalidName(name.to_string()));
+        }
+
+        Ok(self.directory.join(format!("{name}.{PRESET_EXTENSION}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_round_trip() {
+        let directory =
+            std::env::temp_dir().join(format!("voxel-presets-test-{}", std::process::id()));
+        // Presets left over from an earlier run with the same process identifier would be listed
+        let _ = std::fs::remove_dir_all(&directory);
+        let library = PresetLibrary::new(directory);
+
+        let mut options = WorldGenerationOptions {
+            seed: 42,
+            ..Default::default()
+        };
+        options.hill_options.amount = 0.5;
+        library.save("hilly", &options).expect("to save preset");
+
+        let copy_name = library.duplicate("hilly").expect("to duplicate preset");
+        assert_eq!(copy_name, "hilly copy");
+        assert_eq!(library.load(&copy_name).expect("to load preset"), options);
+        assert_eq!(
+            library.names().expect("to list presets"),
+            ["hilly", "hilly copy"]
+        );
+
+        assert!(matches!(
+            library.save("../escape", &options),
+            Err(PresetError::InvalidName(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&library.directory);
+    }
+
+    #[test]
+    fn missing_options_take_default_values() {
+        let options: WorldGenerationOptions =
+            ron::from_str("(seed: 7, hill_options: (twist: 2.0))").expect("to parse preset");
+
+        assert_eq!(options.seed, 7);
+        assert_eq!(options.hill_options.twist, 2.0);
+        assert_eq!(
+            options.hill_options.amount,
+            WorldGenerationOptions::default().hill_options.amount
+        );
+        assert_eq!(
+            options.max_height,
+            WorldGenerationOptions::default().max_height
+        );
+    }
+}
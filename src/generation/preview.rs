@@ -0,0 +1,140 @@
+use glam::FloatExt;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use super::{TerrainSampler, TerrainVoxels, WorldGenerationOptions};
+use crate::chunk::{registry::VoxelRegistry, Voxel};
+
+/// Smallest number of rows sampled by a worker thread at once, as every worker thread builds its
+/// own noise module.
+const ROWS_PER_TASK: usize = 16;
+
+/// Rectangular area of the world, seen from above, covered by a heightmap.
+///
+/// Positions in the area are given on the x and z axes of the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightmapArea {
+    /// Center of the area.
+    pub center: glam::DVec2,
+    /// Size of the area, in voxels.
+    pub extent: glam::DVec2,
+    /// Number of samples along each axis.
+    pub resolution: glam::UVec2,
+}
+
+impl HeightmapArea {
+    /// Returns the position in the world of the sample at the given pixel.
+    pub fn sample_position(&self, pixel: glam::UVec2) -> glam::DVec2 {
+        let sample_size = self.extent / self.resolution.as_dvec2();
+        self.center - self.extent / 2.0 + (pixel.as_dvec2() + 0.5) * sample_size
+    }
+
+    /// Returns the position of the given world position within the area, from `(0, 0)` at the
+    /// minimum corner to `(1, 1)` at the maximum corner.
+    ///
+    /// Returns `None` if the position is outside of the area.
+    pub fn normalized_position(&self, position: glam::DVec2) -> Option<glam::DVec2> {
+        let normalized = (position - (self.center - self.extent / 2.0)) / self.extent;
+        (normalized.cmpge(glam::DVec2::ZERO).all() && normalized.cmple(glam::DVec2::ONE).all())
+            .then_some(normalized)
+    }
+}
+
+/// Top-down heightmap of the terrain, sampled from the world generation noise.
+#[derive(Debug, Clone)]
+pub struct Heightmap {
+    /// Area of the world covered by the heightmap.
+    pub area: HeightmapArea,
+    /// Height of the terrain surface at every sample, in voxels, stored row by row.
+    pub heights: Vec<i32>,
+    /// Voxel visible from above at every sample, stored row by row.
+    pub surface: Vec<Voxel>,
+}
+
+impl Heightmap {
+    /// Samples the terrain over the given area.
+    ///
+    /// Surface voxels follow the same rules as [`super::generate_chunk`], so the heightmap matches
+    /// the generated world.
+    pub fn sample(
+        options: &WorldGenerationOptions,
+        area: HeightmapArea,
+        registry: &VoxelRegistry,
+    ) -> Self {
+        let voxels = TerrainVoxels::new(options, registry);
+
+        // Rows are sampled in parallel, every thread with its own sampler, as noise modules can't
+        // be shared between threads
+        let samples = (0..area.resolution.y)
+            .into_par_iter()
+            .with_min_len(ROWS_PER_TASK)
+            .map_init(
+                || TerrainSampler::new(options),
+                |sampler, y| {
+                    (0..area.resolution.x)
+                        .map(|x| {
+                            let position = area.sample_position(glam::uvec2(x, y)).floor();
+                            let terrain_height = sampler.terrain_height(position.x, position.y);
+                            let surface_height = sampler.surface_height(
+                                position.x as i32,
+                                position.y as i32,
+                                terrain_height,
+                            );
+
+                            // The topmost voxel of the column is either the terrain surface or the
+                            // sea
+                            let surface = if surface_height < options.sea_level_voxels() {
+                                voxels.water
+                            } else {
+                                let climate =
+                                    sampler.climate(position.x, position.y, terrain_height);
+                                voxels.layer_voxel(options, climate, 0, surface_height)
+                            };
+
+                            (surface_height, surface)
+                        })
+                        .collect::<Vec<_>>()
+                },
+            )
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let (heights, surface) = samples.into_iter().unzip();
+        Self {
+            area,
+            heights,
+            surface,
+        }
+    }
+
+    /// Returns the height of every sample as a greyscale value, from black at the bottom of the
+    /// world to white at its maximum height.
+    pub fn greyscale(&self, options: &WorldGenerationOptions) -> Vec<u8> {
+        self.heights
+            .iter()
+            .map(|height| {
+                let height = (*height as f32 / options.max_height as f32).clamp(0.0, 1.0);
+                (height * 255.0).round() as u8
+            })
+            .collect()
+    }
+
+    /// Returns the RGBA color of every sample, stored row by row.
+    ///
+    /// Samples are colored by their surface voxel, and shaded by their height so that the relief
+    /// remains visible.
+    pub fn colors(&self, options: &WorldGenerationOptions, registry: &VoxelRegistry) -> Vec<u8> {
+        self.heights
+            .iter()
+            .zip(&self.surface)
+            .flat_map(|(height, voxel)| {
+                let [r, g, b, _] = registry.color(*voxel);
+                let shade = (*height as f32).remap(0.0, options.max_height as f32, 0.6, 1.2);
+
+                [r, g, b]
+                    .map(|channel| ((channel * shade).clamp(0.0, 1.0) * 255.0) as u8)
+                    .into_iter()
+                    .chain([u8::MAX])
+            })
+            .collect()
+    }
+}
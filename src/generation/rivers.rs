@@ -1,14 +1,22 @@
 use noise::{Cache, Curve, Min, MultiFractal, NoiseFn, Perlin, RidgedMulti, Seedable, Turbulence};
+use serde::{Deserialize, Serialize};
 
 use super::WorldGenerationOptions;
 
 /// Options for generating rivers.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RiverOptions {
     /// Maximum depth of the rivers.
     pub depth: f64,
 }
 
+impl Default for RiverOptions {
+    fn default() -> Self {
+        Self { depth: 0.0234375 }
+    }
+}
+
 impl RiverOptions {
     /// Creates a noise module that defines the shape of the rivers.
     pub fn as_noise_module(&self, world: &WorldGenerationOptions) -> impl NoiseFn<f64, 2> {
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use glam::Vec3Swizzles;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{registry::VoxelRegistry, Chunk, Voxel};
+
+use super::{position_rng, WorldGenerationOptions};
+
+/// Character of a schematic layer that keeps the voxel already in the world.
+const KEEP_SYMBOL: char = '.';
+
+/// Options for placing structures.
+///
+/// The world is divided into square regions, each of which contains at most one structure. The
+/// structure, its position and its rotation are picked from the seed and the position of the
+/// region, so that every chunk it spans places the same structure.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StructureOptions {
+    /// Width of the regions along the X and Z axes, in voxels.
+    pub region_size: u32,
+    /// Chance of a region containing a structure.
+    pub chance: f64,
+}
+
+impl Default for StructureOptions {
+    fn default() -> Self {
+        Self {
+            region_size: 128,
+            chance: 0.5,
+        }
+    }
+}
+
+/// Contents of a schematic file.
+///
+/// Layers are listed from the bottom up. Every layer is a list of rows along the Z axis, and every
+/// character of a row is a voxel along the X axis, resolved through the palette.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SchematicDefinition {
+    /// Names of the voxels placed for every character, except [`KEEP_SYMBOL`].
+    palette: HashMap<char, String>,
+    /// Voxels of the structure, from the bottom up.
+    layers: Vec<Vec<String>>,
+    /// Number of layers placed below the terrain surface.
+    #[serde(default)]
+    sink: u32,
+    /// Relative chance of the structure being picked for a region.
+    #[serde(default = "default_weight")]
+    weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Errors that can occur while loading structures.
+#[derive(Debug)]
+pub enum StructureError {
+    /// A schematic file could not be read.
+    Io(std::io::Error),
+    /// A schematic file is not valid.
+    Parse(String, ron::error::SpannedError),
+    /// A schematic has no voxels, or layers or rows of different sizes.
+    InvalidLayout(String),
+    /// A schematic uses a character that is not in its palette.
+    UnknownSymbol(String, char),
+    /// A schematic palette refers to a voxel that is not registered.
+    UnknownVoxel(String, String),
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read structures: {error}"),
+            Self::Parse(name, error) => write!(f, "failed to parse structure `{name}`: {error}"),
+            Self::InvalidLayout(name) => write!(
+                f,
+                "structure `{name}` must have layers and rows of the same, non-zero size"
+            ),
+            Self::UnknownSymbol(name, symbol) => {
+                write!(
+                    f,
+                    "structure `{name}` uses `{symbol}`, which is not in its palette"
+                )
+            }
+            Self::UnknownVoxel(name, voxel) => {
+                write!(f, "structure `{name}` uses unknown voxel `{voxel}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StructureError {}
+
+/// Prefab structure of voxels, placed on the terrain.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    name: String,
+    size: glam::UVec3,
+    /// Voxels of the structure, or `None` where the voxel in the world is kept.
+    voxels: Vec<Option<Voxel>>,
+    sink: u32,
+    weight: u32,
+}
+
+impl Schematic {
+    /// Creates a schematic from the contents of a RON schematic file, resolving its voxels from
+    /// the registry.
+    pub fn from_ron(
+        name: &str,
+        source: &str,
+        registry: &VoxelRegistry,
+    ) -> Result<Self, StructureError> {
+        let definition: SchematicDefinition = ron::from_str(source)
+            .map_err(|error| StructureError::Parse(name.to_string(), error))?;
+
+        let invalid_layout = || StructureError::InvalidLayout(name.to_string());
+        let height = definition.layers.len();
+        let depth = definition.layers.first().map_or(0, Vec::len);
+        let width = definition
+            .layers
+            .first()
+            .and_then(|layer| layer.first())
+            .map_or(0, |row| row.chars().count());
+        if width == 0 || depth == 0 || height == 0 {
+            return Err(invalid_layout());
+        }
+
+        let mut palette = HashMap::new();
+        for (symbol, voxel_name) in &definition.palette {
+            let voxel = registry.by_name(voxel_name).ok_or_else(|| {
+                StructureError::UnknownVoxel(name.to_string(), voxel_name.clone())
+            })?;
+            palette.insert(*symbol, voxel);
+        }
+
+        let mut voxels = Vec::with_capacity(width * depth * height);
+        for layer in &definition.layers {
+            if layer.len() != depth {
+                return Err(invalid_layout());
+            }
+
+            for row in layer {
+                if row.chars().count() != width {
+                    return Err(invalid_layout());
+                }
+
+                for symbol in row.chars() {
+                    let voxel = if symbol == KEEP_SYMBOL {
+                        None
+                    } else {
+                        let voxel = palette
+                            .get(&symbol)
+                            .ok_or(StructureError::UnknownSymbol(name.to_string(), symbol))?;
+                        Some(*voxel)
+                    };
+                    voxels.push(voxel);
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            size: glam::uvec3(width as u32, height as u32, depth as u32),
+            voxels,
+            sink: definition.sink,
+            weight: definition.weight,
+        })
+    }
+
+    /// Returns the name of the schematic.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the size of the schematic, in voxels.
+    pub fn size(&self) -> glam::UVec3 {
+        self.size
+    }
+
+    /// Returns the voxel at the given position of the schematic, or `None` where the voxel in the
+    /// world is kept.
+    fn get(&self, position: glam::UVec3) -> Option<Voxel> {
+        let index = position.x + position.z * self.size.x + position.y * self.size.x * self.size.z;
+        self.voxels[index as usize]
+    }
+}
+
+/// Every schematic structures are picked from.
+#[derive(Debug, Clone, Default)]
+pub struct StructureLibrary {
+    schematics: Vec<Schematic>,
+}
+
+impl StructureLibrary {
+    /// Creates a library from the given schematics.
+    pub fn new(schematics: Vec<Schematic>) -> Self {
+        Self { schematics }
+    }
+
+    /// Loads every `.ron` schematic file in the given directory, named after their file stem.
+    ///
+    /// Schematics are sorted by name, so that the same structures are placed regardless of the
+    /// order the files are listed in.
+    pub fn load_directory(
+        directory: impl AsRef<Path>,
+        registry: &VoxelRegistry,
+    ) -> Result<Self, StructureError> {
+        let mut schematics = Vec::new();
+        for entry in std::fs::read_dir(directory).map_err(StructureError::Io)? {
+            let path = entry.map_err(StructureError::Io)?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("ron") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let source = std::fs::read_to_string(&path).map_err(StructureError::Io)?;
+            schematics.push(Schematic::from_ron(name, &source, registry)?);
+        }
+
+        schematics.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self::new(schematics))
+    }
+
+    /// Returns every schematic in the library.
+    pub fn schematics(&self) -> &[Schematic] {
+        &self.schematics
+    }
+
+    /// Returns the largest width of any schematic along the X or Z axis, in voxels.
+    fn max_footprint(&self) -> i32 {
+        self.schematics
+            .iter()
+            .map(|schematic| schematic.size.x.max(schematic.size.z) as i32)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Structure placed in a region of the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placement {
+    /// Index of the schematic in the library.
+    schematic: usize,
+    /// Position of the lowest corner of the structure in the world.
+    origin: glam::IVec3,
+    /// Number of quarter turns the structure is rotated by around the Y axis.
+    rotation: u32,
+}
+
+impl Placement {
+    /// Returns the size of the rotated structure.
+    fn size(&self, schematic: &Schematic) -> glam::IVec3 {
+        let size = schematic.size.as_ivec3();
+        if self.rotation.is_multiple_of(2) {
+            size
+        } else {
+            glam::ivec3(size.z, size.y, size.x)
+        }
+    }
+
+    /// Returns the position in the world of the given position of the schematic.
+    fn world_position(&self, schematic: &Schematic, position: glam::UVec3) -> glam::IVec3 {
+        let size = schematic.size.as_ivec3();
+        let position = position.as_ivec3();
+        let rotated = match self.rotation % 4 {
+            0 => position,
+            1 => glam::ivec3(size.z - 1 - position.z, position.y, position.x),
+            2 => glam::ivec3(size.x - 1 - position.x, position.y, size.z - 1 - position.z),
+            _ => glam::ivec3(position.z, position.y, size.x - 1 - position.x),
+        };
+        self.origin + rotated
+    }
+}
+
+impl StructureOptions {
+    /// Returns the structure placed in the given region, if any.
+    ///
+    /// `terrain_height` returns the height of the terrain in a column of the world. Structures
+    /// stand on the terrain at their center, and are never placed below the sea level.
+    fn placement(
+        &self,
+        world: &WorldGenerationOptions,
+        library: &StructureLibrary,
+        terrain_height: &impl Fn(i32, i32) -> i32,
+        region: glam::IVec2,
+    ) -> Option<Placement> {
+        let total_weight = library
+            .schematics
+            .iter()
+            .map(|schematic| schematic.weight)
+            .sum::<u32>();
+        if total_weight == 0 {
+            return None;
+        }
+
+        let mut rng = position_rng(world, 700, glam::ivec3(region.x, 0, region.y));
+        if !rng.gen_bool(self.chance.clamp(0.0, 1.0)) {
+            return None;
+        }
+
+        let mut pick = rng.gen_range(0..total_weight);
+        let schematic_index = library
+            .schematics
+            .iter()
+            .position(|schematic| {
+                let picked = pick < schematic.weight;
+                pick = pick.saturating_sub(schematic.weight);
+                picked
+            })
+            .expect("to pick a schematic within the total weight");
+        let schematic = &library.schematics[schematic_index];
+
+        let mut placement = Placement {
+            schematic: schematic_index,
+            origin: glam::IVec3::ZERO,
+            rotation: rng.gen_range(0..4),
+        };
+        let size = placement.size(schematic);
+
+        let region_size = self.region_size.max(1) as i32;
+        let region_origin = region * region_size;
+        let free_space = (glam::IVec2::splat(region_size) - size.xz()).max(glam::IVec2::ZERO);
+        let corner = region_origin
+            + glam::ivec2(
+                rng.gen_range(0..=free_space.x),
+                rng.gen_range(0..=free_space.y),
+            );
+
+        let center = corner + size.xz() / 2;
+        let ground_height = terrain_height(center.x, center.y);
+        if ground_height < world.sea_level_voxels() {
+            return None;
+        }
+
+        placement.origin = glam::ivec3(
+            corner.x,
+            ground_height + 1 - schematic.sink as i32,
+            corner.y,
+        );
+        Some(placement)
+    }
+
+    /// Writes the parts of the structures that fall into the chunk.
+    ///
+    /// `terrain_height` returns the height of the terrain in a column of the world, see
+    /// [`WorldGenerationOptions::terrain_height`].
+    pub(super) fn place_structures(
+        &self,
+        world: &WorldGenerationOptions,
+        library: &StructureLibrary,
+        terrain_height: impl Fn(i32, i32) -> i32,
+        chunk: &mut Chunk,
+    ) {
+        if library.schematics.is_empty() || self.chance <= 0.0 {
+            return;
+        }
+
+        let chunk_size = chunk.size().as_ivec3();
+        let chunk_min = chunk.grid_position * chunk_size;
+        let chunk_max = chunk_min + chunk_size - 1;
+
+        // Structures starting in earlier regions may reach into the chunk
+        let region_size = self.region_size.max(1) as i32;
+        let min_region =
+            (chunk_min.xz() - library.max_footprint()).div_euclid(glam::IVec2::splat(region_size));
+        let max_region = chunk_max.xz().div_euclid(glam::IVec2::splat(region_size));
+
+        for region_x in min_region.x..=max_region.x {
+            for region_z in min_region.y..=max_region.y {
+                let region = glam::ivec2(region_x, region_z);
+                let Some(placement) = self.placement(world, library, &terrain_height, region)
+                else {
+                    continue;
+                };
+
+                let schematic = &library.schematics[placement.schematic];
+                let structure_min = placement.origin;
+                let structure_max = placement.origin + placement.size(schematic) - 1;
+                if structure_max.cmplt(chunk_min).any() || structure_min.cmpgt(chunk_max).any() {
+                    continue;
+                }
+
+                for x in 0..schematic.size.x {
+                    for y in 0..schematic.size.y {
+                        for z in 0..schematic.size.z {
+                            let position = glam::uvec3(x, y, z);
+                            let Some(voxel) = schematic.get(position) else {
+                                continue;
+                            };
+
+                            let local = placement.world_position(schematic, position) - chunk_min;
+                            if local.cmpge(glam::IVec3::ZERO).all() && local.cmplt(chunk_size).all()
+                            {
+                                chunk.set_voxel(local.as_uvec3(), voxel);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOWER: &str = r#"(
+        palette: { 's': "stone", '_': "air" },
+        layers: [
+            ["sss", "sss", "sss"],
+            ["s.s", "._s", "sss"],
+            ["sss", "sss", "sss"],
+        ],
+    )"#;
+
+    #[test]
+    fn schematics_are_resolved_from_the_registry() {
+        let registry = VoxelRegistry::default();
+        let schematic = Schematic::from_ron("tower", TOWER, &registry).unwrap();
+
+        assert_eq!(schematic.size(), glam::uvec3(3, 3, 3));
+        assert_eq!(schematic.get(glam::uvec3(0, 1, 1)), None);
+        assert_eq!(schematic.get(glam::uvec3(1, 1, 1)), Some(Voxel::AIR));
+        assert_eq!(
+            schematic.get(glam::uvec3(2, 1, 1)),
+            registry.by_name("stone")
+        );
+
+        let unknown = TOWER.replace("\"stone\"", "\"marble\"");
+        assert!(matches!(
+            Schematic::from_ron("tower", &unknown, &registry),
+            Err(StructureError::UnknownVoxel(_, voxel)) if voxel == "marble"
+        ));
+    }
+
+    #[test]
+    fn structures_are_written_into_every_chunk_they_span() {
+        let registry = VoxelRegistry::default();
+        let world = WorldGenerationOptions::default();
+        let tower = Schematic::from_ron("tower", TOWER, &registry).unwrap();
+        let library = StructureLibrary::new(vec![tower.clone()]);
+        let options = StructureOptions {
+            region_size: 8,
+            chance: 1.0,
+        };
+        let terrain_height = |_: i32, _: i32| world.sea_level_voxels() + 2;
+
+        // Chunks of a single voxel make every structure span many chunks
+        let region = glam::IVec2::ZERO;
+        let placement = options
+            .placement(&world, &library, &terrain_height, region)
+            .unwrap();
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    let position = glam::uvec3(x, y, z);
+                    let world_position = placement.world_position(&tower, position);
+                    let mut chunk = Chunk::new(world_position, glam::UVec3::ONE);
+                    options.place_structures(&world, &library, terrain_height, &mut chunk);
+
+                    let expected = tower.get(position).unwrap_or(Voxel::AIR);
+                    assert_eq!(chunk.get_voxel(glam::UVec3::ZERO), Some(&expected));
+                }
+            }
+        }
+    }
+}
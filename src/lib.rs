@@ -0,0 +1,13 @@
+#[macro_use]
+extern crate glium;
+
+pub mod app;
+pub mod block_outline;
+pub mod camera;
+pub mod chunk;
+pub mod generation;
+pub mod sky_dome;
+pub mod transform;
+pub mod ui;
+pub mod utils;
+pub mod world;
@@ -1,32 +1,60 @@
-#[macro_use]
-extern crate glium;
-use std::rc::Rc;
-
-use app::{App, AppBehaviour, Window};
-use camera::{Camera, CameraController, Projection};
-use chunk::VoxelUniforms;
-use generation::{
-    hills::HillOptions, mountains::MountainOptions, plains::PlainOptions, rivers::RiverOptions,
-    WorldGenerationOptions,
-};
+use std::{rc::Rc, sync::Arc};
+
 use glium::Surface;
-use sky_dome::SkyDome;
-use ui::WorldGeneratorUi;
+use voxel::{
+    app::{App, AppBehaviour, Window},
+    block_outline::BlockOutline,
+    camera::{Camera, CameraController, Projection},
+    chunk::{registry::VoxelRegistry, Mesher, Voxel, VoxelUniforms},
+    generation::{presets::PresetLibrary, structures::StructureLibrary, WorldGenerationOptions},
+    sky_dome::SkyDome,
+    ui::WorldGeneratorUi,
+    world::{raycast::RaycastHit, World},
+};
 use winit::{
-    event::{DeviceEvent, ElementState, Event, KeyEvent, WindowEvent},
+    event::{
+        DeviceEvent, ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent,
+    },
     keyboard::{KeyCode, PhysicalKey},
 };
-use world::World;
-
-mod app;
-mod camera;
-mod chunk;
-mod generation;
-mod sky_dome;
-mod transform;
-mod ui;
-mod utils;
-mod world;
+
+/// Path to the voxel definition file, relative to the working directory.
+const VOXEL_DEFINITIONS_PATH: &str = "assets/voxels.ron";
+/// Directory of structure schematics, relative to the working directory.
+const STRUCTURES_DIRECTORY: &str = "assets/structures";
+/// Directory of world generation presets, relative to the working directory.
+const PRESETS_DIRECTORY: &str = "presets";
+/// Name of the preset loaded at startup.
+const DEFAULT_PRESET: &str = "default";
+/// Directory worlds are saved to, relative to the working directory.
+const SAVES_DIRECTORY: &str = "saves";
+
+/// Largest distance, in voxels, at which voxels can be broken and placed.
+const REACH: f32 = 8.0;
+/// Keys selecting the first voxels of the hotbar.
+const HOTBAR_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Returns the directory the world generated with the given options is saved to.
+///
+/// Worlds generated from the same seed with different options are saved separately, so that
+/// chunks saved with old options never end up next to chunks generated with new ones.
+fn save_directory(options: &WorldGenerationOptions) -> std::path::PathBuf {
+    std::path::Path::new(SAVES_DIRECTORY).join(format!(
+        "{}-{:016x}",
+        options.seed,
+        options.fingerprint()
+    ))
+}
 
 struct VoxelApp {
     window: Rc<Window>,
@@ -38,10 +66,14 @@ struct VoxelApp {
 
     sky_dome: SkyDome,
     voxel_shader: glium::Program,
+    block_outline: BlockOutline,
 
+    voxel_registry: Arc<VoxelRegistry>,
     world: World,
     world_generation_options: WorldGenerationOptions,
     world_generator_ui: WorldGeneratorUi,
+    /// Voxel the camera is looking at, within reach.
+    target: Option<RaycastHit>,
 
     render_wireframe: bool,
 }
@@ -81,13 +113,62 @@ impl AppBehaviour for VoxelApp {
                             self.is_cursor_hidden = true;
                         }
 
+                        // Number keys are left to the UI while the cursor is released
+                        if let Some(index) = HOTBAR_KEYS.iter().position(|hotbar_key| {
+                            *hotbar_key == key && state == ElementState::Pressed
+                        }) {
+                            if self.is_cursor_hidden {
+                                self.world_generator_ui.hotbar.select(index);
+                            }
+                        }
+
                         if key == KeyCode::F3 && state == ElementState::Pressed {
                             self.render_wireframe = !self.render_wireframe;
                         }
 
+                        if key == KeyCode::F4 && state == ElementState::Pressed {
+                            self.world.set_mesher(match self.world.mesher() {
+                                Mesher::Greedy => Mesher::Binary,
+                                Mesher::Binary => Mesher::Greedy,
+                            });
+                        }
+
+                        if key == KeyCode::F5 && state == ElementState::Pressed {
+                            if let Err(error) = self.world.save() {
+                                eprintln!("Failed to save world: {error}");
+                            }
+                        }
+
                         self.camera_controller.process_keyboard(key, state);
                         true
                     }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button,
+                        ..
+                    } if self.is_cursor_hidden => {
+                        match button {
+                            MouseButton::Left if self.world_generator_ui.brush_panel.enabled => {
+                                self.sculpt_at_target()
+                            }
+                            MouseButton::Left => self.break_target(),
+                            MouseButton::Right => self.place_at_target(),
+                            _ => {}
+                        }
+                        true
+                    }
+                    WindowEvent::MouseWheel { delta, .. } if self.is_cursor_hidden => {
+                        let scroll = match delta {
+                            MouseScrollDelta::LineDelta(_, y) => y,
+                            MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                        };
+                        if scroll != 0.0 {
+                            self.world_generator_ui
+                                .hotbar
+                                .scroll(-scroll.signum() as i32);
+                        }
+                        true
+                    }
                     _ => true,
                 }
             }
@@ -112,14 +193,31 @@ impl AppBehaviour for VoxelApp {
 
         self.sky_dome.position = self.camera.position - glam::vec3(0.0, 200.0, 0.0);
 
+        self.world
+            .set_memory_budget(self.world_generator_ui.memory_budget);
+        self.world
+            .set_frame_budget(self.world_generator_ui.frame_budget);
         self.world
             .update(self.camera.position, &self.world_generation_options);
+        self.target =
+            self.world
+                .raycast(self.camera.position, self.camera.front(), REACH, |voxel| {
+                    !self.voxel_registry.is_liquid(voxel)
+                });
+        self.world_generator_ui.world_stats = self.world.stats();
+        self.world_generator_ui.camera_position = self.camera.position;
+        self.world_generator_ui.target = self.target.map(|target| target.position);
 
         if self.world_generator_ui.should_generate_world {
             self.world_generator_ui.should_generate_world = false;
 
+            if let Err(error) = self.world.save() {
+                eprintln!("Failed to save world: {error}");
+            }
             self.world.clear();
-            self.world_generation_options = self.world_generator_ui.world_generator_options.clone();
+            self.world_generation_options = self.world_generator_ui.world_generator_options;
+            self.world
+                .set_save_directory(save_directory(&self.world_generation_options));
         }
     }
 
@@ -136,18 +234,74 @@ impl AppBehaviour for VoxelApp {
             VoxelUniforms {
                 view_projection: view_projection.to_cols_array_2d(),
                 light_color: [1.0, 1.0, 1.0],
-                light_position: [100.0, 100.0, 100.0],
+                light_direction: [0.4, 1.0, 0.3],
             },
             self.render_wireframe,
         );
 
+        if let Some(target) = self.target {
+            self.block_outline
+                .draw(frame, view_projection, target.position);
+        }
+
         self.sky_dome.draw(frame, view_projection);
 
         self.world_generator_ui.render(frame);
     }
+
+    fn exit(&mut self) {
+        if let Err(error) = self.world.save() {
+            eprintln!("Failed to save world: {error}");
+        }
+    }
 }
 
 impl VoxelApp {
+    /// Removes the voxel the camera is looking at.
+    fn break_target(&mut self) {
+        if let Some(target) = self.target.take() {
+            self.world.set_voxel(target.position, Voxel::AIR);
+        }
+    }
+
+    /// Applies a stroke of the brush around the voxel the camera is looking at.
+    ///
+    /// Like placed voxels, the brush never fills the voxel the camera is in.
+    fn sculpt_at_target(&mut self) {
+        if let Some(target) = self.target.take() {
+            let camera_position = self.camera.position.floor().as_ivec3();
+            let mut edits = self.world_generator_ui.brush_panel.brush.stroke(
+                &target,
+                &self.voxel_registry,
+                |position| self.world.get_voxel(position),
+            );
+            edits.retain(|(position, voxel)| *position != camera_position || voxel.is_air());
+            self.world.set_voxels(edits);
+        }
+    }
+
+    /// Places the voxel selected in the hotbar against the face of the voxel the camera is looking
+    /// at.
+    ///
+    /// Voxels only replace air and liquids, and are never placed where the camera is.
+    fn place_at_target(&mut self) {
+        let (Some(target), Some(voxel)) = (
+            self.target.take(),
+            self.world_generator_ui.hotbar.selected_voxel(),
+        ) else {
+            return;
+        };
+
+        let position = target.position + target.normal();
+        let is_replaceable = self
+            .world
+            .get_voxel(position)
+            .is_some_and(|current| current.is_air() || self.voxel_registry.is_liquid(current));
+        if is_replaceable && position != self.camera.position.floor().as_ivec3() {
+            self.world.set_voxel(position, voxel);
+        }
+    }
+
     fn new(window: Rc<Window>, event_loop: &winit::event_loop::EventLoop<()>) -> Self {
         window
             .winit
@@ -186,41 +340,41 @@ impl VoxelApp {
         };
 
         let sky_dome = SkyDome::new(&window.display, 20, 20, 500.0);
-
-        let world_generation_options = WorldGenerationOptions {
-            seed: 1337,
-            chunk_size: glam::UVec3::splat(32),
-            max_height: 128,
-            dirt_layer_thickness: 5,
-
-            continent_frequency: 0.001,
-            continent_lacunarity: 2.208984375,
-            continent_height_scale: 0.25,
-            sea_level: 0.0,
-
-            shelf_level: -0.375,
-            terrain_offset: 1.0,
-
-            mountain_options: MountainOptions {
-                lacunarity: 2.142578125,
-                twist: 0.5,
-                glaciation: 1.375,
-                amount: 0.5,
-            },
-            hill_options: HillOptions {
-                lacunarity: 2.162109375,
-                twist: 1.0,
-                amount: 1.5 / 2.0,
-            },
-            plain_options: PlainOptions {
-                lacunarity: 2.314453125,
-            },
-            river_options: RiverOptions { depth: 0.0234375 },
-        };
-
-        let world = World::new(window.clone(), 1);
-        let world_generator_ui =
-            WorldGeneratorUi::new(world_generation_options, window.clone(), event_loop);
+        let block_outline = BlockOutline::new(&window.display);
+
+        let presets = PresetLibrary::new(PRESETS_DIRECTORY);
+        let world_generation_options = presets.load(DEFAULT_PRESET).unwrap_or_else(|error| {
+            eprintln!(
+                "Failed to load the {DEFAULT_PRESET} preset, using the default options: {error}"
+            );
+            WorldGenerationOptions::default()
+        });
+
+        let voxel_registry = VoxelRegistry::load(VOXEL_DEFINITIONS_PATH).unwrap_or_else(|error| {
+            eprintln!("Failed to load {VOXEL_DEFINITIONS_PATH}, using the bundled voxels: {error}");
+            VoxelRegistry::default()
+        });
+        let voxel_registry = Arc::new(voxel_registry);
+
+        let structures = StructureLibrary::load_directory(STRUCTURES_DIRECTORY, &voxel_registry)
+            .unwrap_or_else(|error| {
+                eprintln!(
+                    "Failed to load {STRUCTURES_DIRECTORY}, generating no structures: {error}"
+                );
+                StructureLibrary::default()
+            });
+        let structures = Arc::new(structures);
+
+        let mut world = World::new(window.clone(), 1, voxel_registry.clone(), structures);
+        world.set_save_directory(save_directory(&world_generation_options));
+        let world_generator_ui = WorldGeneratorUi::new(
+            world_generation_options,
+            presets,
+            DEFAULT_PRESET,
+            voxel_registry.clone(),
+            window.clone(),
+            event_loop,
+        );
 
         Self {
             window,
@@ -232,10 +386,13 @@ impl VoxelApp {
 
             sky_dome,
             voxel_shader,
+            block_outline,
 
+            voxel_registry,
             world,
             world_generation_options,
             world_generator_ui,
+            target: None,
 
             render_wireframe: false,
         }
@@ -0,0 +1,9 @@
+#version 140
+
+out vec4 color;
+
+uniform vec4 outline_color;
+
+void main() {
+    color = outline_color;
+}
@@ -3,21 +3,35 @@
 in vec4 vertex_color;
 in vec3 vertex_normal;
 in vec3 frag_pos;
+in float vertex_ambient_occlusion;
+in vec2 vertex_light;
 
 out vec4 color;
 
-uniform vec3 light_position;
+uniform vec3 light_direction;
 uniform vec3 light_color;
 
+// Color of the light emitted by voxels
+const vec3 block_light_color = vec3(1.0, 0.85, 0.6);
+// Brightness lost for every light level below the maximum
+const float light_falloff = 0.8;
+
+// Converts a light level from 0 to 1 into a brightness
+float brightness(float level) {
+    return pow(light_falloff, 15.0 * (1.0 - level));
+}
+
 void main() {
     float ambient_strength = 0.1;
-    vec3 ambient = ambient_strength * light_color;
 
     vec3 norm = normalize(vertex_normal);
-    vec3 light_dir = normalize(light_position - frag_pos);
-    float diff = max(dot(norm, light_dir), 0.0);
-    vec3 diffuse = diff * light_color;
+    float diff = max(dot(norm, normalize(light_direction)), 0.0);
+
+    // Sunlight only reaches surfaces open to the sky
+    vec3 sun = (ambient_strength + 0.3 + 0.6 * diff) * light_color * brightness(vertex_light.x);
+    vec3 block = block_light_color * brightness(vertex_light.y);
+    vec3 ambient = vec3(0.02);
 
-    vec3 result = (ambient + diffuse) * vertex_color.xyz;
+    vec3 result = (ambient + max(sun, block)) * vertex_color.xyz * vertex_ambient_occlusion;
     color = vec4(pow(result, vec3(1.0 / 2.2)), vertex_color.a);
-}
\ No newline at end of file
+}
@@ -3,10 +3,14 @@
 in vec3 position;
 in vec3 normal;
 in vec4 color;
+in float ambient_occlusion;
+in vec2 light;
 
 out vec4 vertex_color;
 out vec3 vertex_normal;
 out vec3 frag_pos;
+out float vertex_ambient_occlusion;
+out vec2 vertex_light;
 
 uniform mat4 view_proj;
 uniform mat4 model;
@@ -15,7 +19,9 @@ uniform mat3 normal_matrix;
 void main() {
     vertex_color = color;
     vertex_normal = normal_matrix * normal;
+    vertex_ambient_occlusion = ambient_occlusion;
+    vertex_light = light;
 
     frag_pos = vec3(model * vec4(position, 1.0));
     gl_Position = view_proj * model * vec4(position, 1.0);
-}
\ No newline at end of file
+}
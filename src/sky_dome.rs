@@ -44,7 +44,7 @@ impl SkyDome {
         frame
             .draw(
                 &self.vertex_buffer,
-                &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+                glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
                 &self.program,
                 &glium::uniform! {
                     mvp: (view_projection * sky_dome_model).to_cols_array_2d(),
@@ -1,8 +1,25 @@
-use std::rc::Rc;
+use std::{rc::Rc, sync::Arc, time::Duration};
 
+pub use brush_panel::BrushPanel;
+use generation_settings::generation_settings;
+pub use hotbar::Hotbar;
+use preview::HeightmapPreview;
 use winit::{event::WindowEvent, event_loop::EventLoop};
 
-use crate::{app::Window, generation::WorldGenerationOptions};
+use crate::{
+    app::Window,
+    chunk::registry::VoxelRegistry,
+    generation::{presets::PresetLibrary, WorldGenerationOptions},
+    world::{WorldStats, DEFAULT_FRAME_BUDGET, DEFAULT_MEMORY_BUDGET},
+};
+
+mod brush_panel;
+mod generation_settings;
+mod hotbar;
+mod preview;
+
+/// Number of bytes in a mebibyte, used to display memory sizes.
+const MEBIBYTE: usize = 1024 * 1024;
 
 pub struct WorldGeneratorUi {
     window: Rc<Window>,
@@ -11,14 +28,50 @@ pub struct WorldGeneratorUi {
     seed: String,
     pub world_generator_options: WorldGenerationOptions,
     pub should_generate_world: bool,
+
+    /// Library of saved world generation presets.
+    presets: PresetLibrary,
+    /// Names of the presets in the library.
+    preset_names: Vec<String>,
+    /// Name of the preset being edited.
+    preset_name: String,
+    /// Error from the last preset operation, if it failed.
+    preset_error: Option<String>,
+
+    /// Top-down preview of the terrain generated with the current options.
+    heightmap_preview: HeightmapPreview,
+    /// Position of the camera, marked in the preview.
+    pub camera_position: glam::Vec3,
+
+    /// Memory budget for loaded chunks, in bytes.
+    pub memory_budget: usize,
+    /// Time spent integrating finished chunks and meshes every frame.
+    pub frame_budget: Duration,
+    /// Statistics about the world, updated every frame.
+    pub world_stats: WorldStats,
+
+    /// Selector of the voxel placed in the world.
+    pub hotbar: Hotbar,
+    /// Settings of the terrain sculpting brush.
+    pub brush_panel: BrushPanel,
+    /// Position of the voxel the camera is looking at, if any.
+    pub target: Option<glam::IVec3>,
 }
 
 impl WorldGeneratorUi {
     pub fn new(
         world_generator_options: WorldGenerationOptions,
+        presets: PresetLibrary,
+        preset_name: &str,
+        registry: Arc<VoxelRegistry>,
         window: Rc<Window>,
         event_loop: &EventLoop<()>,
     ) -> Self {
+        let (preset_names, preset_error) = match presets.names() {
+            Ok(names) => (names, None),
+            Err(error) => (Vec::new(), Some(error.to_string())),
+        };
+
         Self {
             egui: egui_glium::EguiGlium::new(
                 egui::ViewportId::ROOT,
@@ -31,6 +84,21 @@ impl WorldGeneratorUi {
             seed: world_generator_options.seed.to_string(),
             world_generator_options,
             should_generate_world: false,
+
+            presets,
+            preset_names,
+            preset_name: preset_name.to_string(),
+            preset_error,
+
+            hotbar: Hotbar::new(&registry),
+            brush_panel: BrushPanel::new(&registry),
+            target: None,
+            heightmap_preview: HeightmapPreview::new(registry),
+            camera_position: glam::Vec3::ZERO,
+
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            frame_budget: DEFAULT_FRAME_BUDGET,
+            world_stats: WorldStats::default(),
         }
     }
 
@@ -41,6 +109,60 @@ impl WorldGeneratorUi {
     pub fn render(&mut self, frame: &mut glium::Frame) {
         self.egui.run(&self.window.winit, |ctx| {
             egui::Window::new("World Generator").show(ctx, |ui| {
+                ui.collapsing("Presets", |ui| {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("preset")
+                            .selected_text(&self.preset_name)
+                            .show_ui(ui, |ui| {
+                                for name in &self.preset_names {
+                                    ui.selectable_value(&mut self.preset_name, name.clone(), name);
+                                }
+                            });
+                        ui.text_edit_singleline(&mut self.preset_name);
+                    });
+
+                    ui.horizontal(|ui| {
+                        let mut result = None;
+
+                        if ui.button("Load").clicked() {
+                            result = Some(self.presets.load(&self.preset_name).map(|options| {
+                                self.world_generator_options = options;
+                                self.seed = options.seed.to_string();
+                            }));
+                        }
+
+                        if ui.button("Save").clicked() {
+                            if let Ok(seed) = self.seed.parse() {
+                                self.world_generator_options.seed = seed;
+                            }
+                            result = Some(
+                                self.presets
+                                    .save(&self.preset_name, &self.world_generator_options),
+                            );
+                        }
+
+                        if ui.button("Duplicate").clicked() {
+                            result = Some(
+                                self.presets
+                                    .duplicate(&self.preset_name)
+                                    .map(|name| self.preset_name = name),
+                            );
+                        }
+
+                        if let Some(result) = result {
+                            self.preset_error = result
+                                .and_then(|_| self.presets.names())
+                                .map(|names| self.preset_names = names)
+                                .err()
+                                .map(|error| error.to_string());
+                        }
+                    });
+
+                    if let Some(error) = &self.preset_error {
+                        ui.colored_label(egui::Color32::RED, error);
+                    }
+                });
+
                 ui.horizontal(|ui| {
                     ui.label("Seed:");
 
@@ -61,7 +183,7 @@ impl WorldGeneratorUi {
                         }
                     }
 
-                    if let Err(_) = self.seed.parse::<u32>() {
+                    if self.seed.parse::<u32>().is_err() {
                         ui.label("Seed should be a number.");
                     }
 
@@ -70,54 +192,56 @@ impl WorldGeneratorUi {
                     }
                 });
 
-                ui.collapsing("Size Settings", |ui| {
-                    ui.label("Chunk Size:");
-                    ui.horizontal(|ui| {
-                        ui.add(
-                            egui::Slider::new(
-                                &mut self.world_generator_options.chunk_size.x,
-                                0..=128,
-                            )
-                            .text("X"),
-                        );
-                        ui.add(
-                            egui::Slider::new(
-                                &mut self.world_generator_options.chunk_size.y,
-                                0..=128,
-                            )
-                            .text("Y"),
-                        );
-                        ui.add(
-                            egui::Slider::new(
-                                &mut self.world_generator_options.chunk_size.z,
-                                0..=128,
-                            )
-                            .text("Z"),
-                        );
-                    });
-                });
+                generation_settings(ui, &mut self.world_generator_options);
 
-                ui.collapsing("Continent Settings", |ui| {
-                    ui.add(
-                        egui::Slider::new(
-                            &mut self.world_generator_options.continent_frequency,
-                            0.0001..=0.1,
-                        )
-                        .text("Continent Frequency"),
+                ui.collapsing("Preview", |ui| {
+                    self.heightmap_preview.show(
+                        ui,
+                        &self.world_generator_options,
+                        self.camera_position,
                     );
+                });
+
+                ui.collapsing("World Statistics", |ui| {
+                    let stats = self.world_stats;
+                    ui.label(format!("Loaded Chunks: {}", stats.loaded_chunks));
+                    ui.label(format!("Meshed Chunks: {}", stats.meshed_chunks));
+                    ui.label(format!(
+                        "Queued Jobs: {} generation, {} meshing",
+                        stats.queued_generation_jobs, stats.queued_meshing_jobs
+                    ));
+                    ui.label(format!("Jobs In Flight: {}", stats.jobs_in_flight));
+                    ui.label(format!(
+                        "Last Frame: {} chunks integrated, {} meshes uploaded",
+                        stats.integrated_chunks, stats.uploaded_meshes
+                    ));
+                    ui.label(format!(
+                        "Memory Usage: {:.1} / {:.1} MiB",
+                        stats.memory_usage as f64 / MEBIBYTE as f64,
+                        stats.memory_budget as f64 / MEBIBYTE as f64
+                    ));
 
-                    ui.add(
-                        egui::Slider::new(
-                            &mut self.world_generator_options.continent_lacunarity,
-                            1.5..=2.5,
+                    let mut memory_budget = self.memory_budget / MEBIBYTE;
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut memory_budget, 64..=4096)
+                                .text("Memory Budget (MiB)"),
                         )
-                        .text("Continent Lacunarity"),
-                    );
+                        .changed()
+                    {
+                        self.memory_budget = memory_budget * MEBIBYTE;
+                    }
 
-                    ui.add(
-                        egui::Slider::new(&mut self.world_generator_options.sea_level, -1.0..=1.0)
-                            .text("Sea Level"),
-                    );
+                    let mut frame_budget = self.frame_budget.as_secs_f32() * 1000.0;
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut frame_budget, 0.5..=16.0)
+                                .text("Frame Budget (ms)"),
+                        )
+                        .changed()
+                    {
+                        self.frame_budget = Duration::from_secs_f32(frame_budget / 1000.0);
+                    }
                 });
 
                 ui.separator();
@@ -133,6 +257,9 @@ impl WorldGeneratorUi {
                     self.world_generator_options.seed = self.seed.parse().expect("to parse seed");
                 }
             });
+
+            self.brush_panel.show(ctx, self.target);
+            self.hotbar.show(ctx);
         });
 
         self.egui.paint(&self.window.display, frame);
@@ -0,0 +1,91 @@
+use crate::{
+    chunk::{registry::VoxelRegistry, Voxel},
+    world::brush::{Brush, BrushMode, BrushShape},
+};
+
+/// Largest radius of the brush that can be picked, in voxels.
+const MAX_RADIUS: u32 = 16;
+
+/// Settings of the terrain sculpting brush.
+pub struct BrushPanel {
+    /// Whether clicking in the world sculpts with the brush instead of breaking a single voxel.
+    pub enabled: bool,
+    pub brush: Brush,
+    /// Voxels the brush can place, along with their names.
+    voxels: Vec<(Voxel, String)>,
+}
+
+impl BrushPanel {
+    pub fn new(registry: &VoxelRegistry) -> Self {
+        let voxels: Vec<_> = registry
+            .voxels()
+            .filter(|(voxel, _)| !voxel.is_air())
+            .map(|(voxel, definition)| (voxel, definition.name.clone()))
+            .collect();
+
+        Self {
+            enabled: false,
+            brush: Brush::new(voxels.first().map_or(Voxel::AIR, |(voxel, _)| *voxel)),
+            voxels,
+        }
+    }
+
+    /// Shows the panel, where `target` is the position of the voxel the camera is looking at.
+    pub fn show(&mut self, ctx: &egui::Context, target: Option<glam::IVec3>) {
+        egui::Window::new("Brush")
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.enabled, "Sculpt with left click");
+
+                ui.horizontal(|ui| {
+                    ui.label("Shape:");
+                    ui.selectable_value(&mut self.brush.shape, BrushShape::Sphere, "Sphere");
+                    ui.selectable_value(&mut self.brush.shape, BrushShape::Cube, "Cube");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Mode:");
+                    for (mode, name) in [
+                        (BrushMode::Add, "Add"),
+                        (BrushMode::Remove, "Remove"),
+                        (BrushMode::Smooth, "Smooth"),
+                        (BrushMode::Flatten, "Flatten"),
+                        (BrushMode::Paint, "Paint"),
+                    ] {
+                        ui.selectable_value(&mut self.brush.mode, mode, name);
+                    }
+                });
+
+                ui.add(egui::Slider::new(&mut self.brush.radius, 0..=MAX_RADIUS).text("Radius"));
+
+                let selected_name = self
+                    .voxels
+                    .iter()
+                    .find(|(voxel, _)| *voxel == self.brush.voxel)
+                    .map_or("", |(_, name)| name.as_str());
+                egui::ComboBox::from_label("Material")
+                    .selected_text(selected_name)
+                    .show_ui(ui, |ui| {
+                        for (voxel, name) in &self.voxels {
+                            ui.selectable_value(&mut self.brush.voxel, *voxel, name);
+                        }
+                    });
+
+                ui.add_enabled_ui(self.brush.mode == BrushMode::Flatten, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Target Height:");
+                        ui.add(egui::DragValue::new(&mut self.brush.target_height));
+                        if ui
+                            .add_enabled(target.is_some(), egui::Button::new("Pick"))
+                            .on_hover_text("Use the height of the voxel being looked at.")
+                            .clicked()
+                        {
+                            if let Some(target) = target {
+                                self.brush.target_height = target.y;
+                            }
+                        }
+                    });
+                });
+            });
+    }
+}
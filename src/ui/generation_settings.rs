@@ -0,0 +1,277 @@
+use crate::generation::{
+    biomes::BiomeOptions,
+    caves::CaveOptions,
+    density::DensityOptions,
+    features::FeatureOptions,
+    hills::HillOptions,
+    mountains::MountainOptions,
+    ores::{OreOptions, OreVeinOptions},
+    plains::PlainOptions,
+    rivers::RiverOptions,
+    structures::StructureOptions,
+    WorldGenerationOptions,
+};
+
+/// Draws a button that resets a section of the settings to its default values.
+///
+/// Returns `true` if the button was clicked.
+fn reset_button(ui: &mut egui::Ui) -> bool {
+    ui.button("Reset to Defaults")
+        .on_hover_text("Reset the settings in this section to their default values.")
+        .clicked()
+}
+
+/// Draws the settings of the veins of a single ore.
+fn ore_vein_settings(ui: &mut egui::Ui, name: &str, vein: &mut OreVeinOptions) {
+    ui.label(format!("{name}:"));
+    ui.add(egui::Slider::new(&mut vein.min_height, -256..=vein.max_height).text("Min Height"));
+    ui.add(egui::Slider::new(&mut vein.max_height, vein.min_height..=512).text("Max Height"));
+    ui.add(
+        egui::Slider::new(&mut vein.frequency, 0.0..=8.0)
+            .text("Frequency")
+            .suffix(" per 16³"),
+    )
+    .on_hover_text("Average number of veins in a volume of 16×16×16 voxels.");
+    ui.add(egui::Slider::new(&mut vein.vein_size, 1..=32).text("Vein Size"));
+}
+
+/// Draws a section for every group of world generation options.
+///
+/// Options are kept within their valid range while they are edited.
+pub fn generation_settings(ui: &mut egui::Ui, options: &mut WorldGenerationOptions) {
+    let defaults = WorldGenerationOptions::default();
+
+    ui.collapsing("Size Settings", |ui| {
+        ui.label("Chunk Size:");
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut options.chunk_size.x, 1..=128).text("X"));
+            ui.add(egui::Slider::new(&mut options.chunk_size.y, 1..=128).text("Y"));
+            ui.add(egui::Slider::new(&mut options.chunk_size.z, 1..=128).text("Z"));
+        });
+
+        ui.add(egui::Slider::new(&mut options.max_height, 16..=512).text("Max Height"));
+        ui.add(
+            egui::Slider::new(&mut options.dirt_layer_thickness, 0..=16)
+                .text("Dirt Layer Thickness"),
+        );
+
+        if reset_button(ui) {
+            options.chunk_size = defaults.chunk_size;
+            options.max_height = defaults.max_height;
+            options.dirt_layer_thickness = defaults.dirt_layer_thickness;
+        }
+    });
+
+    ui.collapsing("Continent Settings", |ui| {
+        ui.add(
+            egui::Slider::new(&mut options.continent_frequency, 0.0001..=0.1)
+                .logarithmic(true)
+                .text("Continent Frequency"),
+        );
+        ui.add(
+            egui::Slider::new(&mut options.continent_lacunarity, 1.5..=2.5)
+                .text("Continent Lacunarity"),
+        );
+        ui.add(
+            egui::Slider::new(&mut options.continent_height_scale, 0.0..=1.0)
+                .text("Continent Height Scale"),
+        );
+        ui.add(egui::Slider::new(&mut options.sea_level, -1.0..=1.0).text("Sea Level"));
+        ui.add(
+            egui::Slider::new(&mut options.shelf_level, -1.0..=options.sea_level)
+                .text("Shelf Level"),
+        )
+        .on_hover_text("Elevation of the continental shelf, always below the sea level.");
+        ui.add(egui::Slider::new(&mut options.terrain_offset, 0.0..=2.0).text("Terrain Offset"))
+            .on_hover_text("Low values cause rough terrain to appear at higher elevations.");
+
+        if reset_button(ui) {
+            options.continent_frequency = defaults.continent_frequency;
+            options.continent_lacunarity = defaults.continent_lacunarity;
+            options.continent_height_scale = defaults.continent_height_scale;
+            options.sea_level = defaults.sea_level;
+            options.shelf_level = defaults.shelf_level;
+            options.terrain_offset = defaults.terrain_offset;
+        }
+    });
+
+    ui.collapsing("Mountain Settings", |ui| {
+        let mountains = &mut options.mountain_options;
+        ui.add(egui::Slider::new(&mut mountains.lacunarity, 1.5..=2.5).text("Lacunarity"));
+        ui.add(egui::Slider::new(&mut mountains.twist, 0.0..=2.0).text("Twist"));
+        ui.add(egui::Slider::new(&mut mountains.glaciation, 1.0..=2.0).text("Glaciation"));
+        ui.add(egui::Slider::new(&mut mountains.amount, 0.0..=1.0).text("Amount"));
+
+        if reset_button(ui) {
+            *mountains = MountainOptions::default();
+        }
+    });
+
+    ui.collapsing("Hill Settings", |ui| {
+        let hills = &mut options.hill_options;
+        ui.add(egui::Slider::new(&mut hills.lacunarity, 1.5..=2.5).text("Lacunarity"));
+        ui.add(egui::Slider::new(&mut hills.twist, 0.0..=2.0).text("Twist"));
+        ui.add(egui::Slider::new(&mut hills.amount, 0.0..=1.0).text("Amount"));
+
+        if reset_button(ui) {
+            *hills = HillOptions::default();
+        }
+    });
+
+    ui.collapsing("Plain Settings", |ui| {
+        let plains = &mut options.plain_options;
+        ui.add(egui::Slider::new(&mut plains.lacunarity, 1.5..=2.5).text("Lacunarity"));
+
+        if reset_button(ui) {
+            *plains = PlainOptions::default();
+        }
+    });
+
+    ui.collapsing("River Settings", |ui| {
+        let rivers = &mut options.river_options;
+        ui.add(egui::Slider::new(&mut rivers.depth, 0.0..=0.1).text("Depth"));
+
+        if reset_button(ui) {
+            *rivers = RiverOptions::default();
+        }
+    });
+
+    ui.collapsing("Density Settings", |ui| {
+        let density = &mut options.density_options;
+        ui.add(egui::Slider::new(&mut density.amplitude, 0.0..=32.0).text("Amplitude"))
+            .on_hover_text(
+                "How far overhangs reach out of the terrain, in voxels. Zero disables them.",
+            );
+        ui.add(
+            egui::Slider::new(&mut density.frequency, 0.001..=0.2)
+                .logarithmic(true)
+                .text("Frequency"),
+        );
+        ui.add(egui::Slider::new(&mut density.vertical_scale, 0.25..=4.0).text("Vertical Scale"));
+        ui.add(egui::Slider::new(&mut density.octaves, 1..=6).text("Octaves"));
+
+        if reset_button(ui) {
+            *density = DensityOptions::default();
+        }
+    });
+
+    ui.collapsing("Cave Settings", |ui| {
+        let caves = &mut options.cave_options;
+        ui.add(
+            egui::Slider::new(&mut caves.spaghetti_frequency, 0.001..=0.1)
+                .logarithmic(true)
+                .text("Tunnel Frequency"),
+        );
+        ui.add(
+            egui::Slider::new(&mut caves.spaghetti_thickness, 0.0..=0.3).text("Tunnel Thickness"),
+        )
+        .on_hover_text("Zero disables tunnels.");
+        ui.add(
+            egui::Slider::new(&mut caves.cheese_frequency, 0.001..=0.1)
+                .logarithmic(true)
+                .text("Cavern Frequency"),
+        );
+        ui.add(egui::Slider::new(&mut caves.cheese_threshold, 0.0..=1.0).text("Cavern Threshold"))
+            .on_hover_text("Higher values make caverns smaller and rarer, one disables them.");
+        ui.add(
+            egui::Slider::new(&mut caves.ocean_floor_thickness, 0..=16)
+                .text("Ocean Floor Thickness"),
+        );
+        ui.checkbox(&mut caves.flooded, "Flood Caves Below Sea Level");
+
+        if reset_button(ui) {
+            *caves = CaveOptions::default();
+        }
+    });
+
+    ui.collapsing("Biome Settings", |ui| {
+        let biomes = &mut options.biome_options;
+        ui.add(
+            egui::Slider::new(&mut biomes.temperature_frequency, 0.0001..=0.05)
+                .logarithmic(true)
+                .text("Temperature Frequency"),
+        );
+        ui.add(
+            egui::Slider::new(&mut biomes.humidity_frequency, 0.0001..=0.05)
+                .logarithmic(true)
+                .text("Humidity Frequency"),
+        );
+        ui.add(egui::Slider::new(&mut biomes.border_blend, 0.0..=0.5).text("Border Blend"))
+            .on_hover_text("How much neighbouring biomes interleave at their borders.");
+        ui.add(egui::Slider::new(&mut biomes.altitude_cooling, 0.0..=2.0).text("Altitude Cooling"))
+            .on_hover_text(
+                "How much colder the climate gets from the sea level to the maximum height.",
+            );
+        ui.add(egui::Slider::new(&mut biomes.beach_height, 0..=16).text("Beach Height"));
+        ui.add(egui::Slider::new(&mut biomes.alpine_altitude, 0.0..=1.0).text("Alpine Altitude"))
+            .on_hover_text(
+                "Altitude above which mountains are bare rock, relative to the sea level.",
+            );
+        ui.add(egui::Slider::new(&mut biomes.snow_altitude, 0.0..=1.0).text("Snow Altitude"))
+            .on_hover_text("Altitude of the snow line, relative to the sea level.");
+
+        if reset_button(ui) {
+            *biomes = BiomeOptions::default();
+        }
+    });
+
+    ui.collapsing("Ore Settings", |ui| {
+        let ores = &mut options.ore_options;
+        ore_vein_settings(ui, "Coal", &mut ores.coal);
+        ore_vein_settings(ui, "Iron", &mut ores.iron);
+        ore_vein_settings(ui, "Gold", &mut ores.gold);
+        ore_vein_settings(ui, "Diamond", &mut ores.diamond);
+
+        if reset_button(ui) {
+            *ores = OreOptions::default();
+        }
+    });
+
+    ui.collapsing("Vegetation Settings", |ui| {
+        let features = &mut options.feature_options;
+        ui.add(
+            egui::Slider::new(&mut features.forest_tree_chance, 0.0..=0.2)
+                .text("Forest Tree Chance"),
+        )
+        .on_hover_text("Chance of a tree growing on a grass voxel in forests.");
+        ui.add(egui::Slider::new(&mut features.tree_chance, 0.0..=0.2).text("Tree Chance"))
+            .on_hover_text("Chance of a tree growing on a grass voxel outside of forests.");
+        ui.add(egui::Slider::new(&mut features.bush_chance, 0.0..=0.2).text("Bush Chance"));
+        ui.add(
+            egui::Slider::new(&mut features.tall_grass_chance, 0.0..=1.0).text("Tall Grass Chance"),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut features.min_trunk_height,
+                1..=features.max_trunk_height,
+            )
+            .text("Min Trunk Height"),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut features.max_trunk_height,
+                features.min_trunk_height..=16,
+            )
+            .text("Max Trunk Height"),
+        );
+        ui.add(egui::Slider::new(&mut features.leaf_radius, 0..=4).text("Leaf Radius"));
+
+        if reset_button(ui) {
+            *features = FeatureOptions::default();
+        }
+    });
+
+    ui.collapsing("Structure Settings", |ui| {
+        let structures = &mut options.structure_options;
+        ui.add(egui::Slider::new(&mut structures.region_size, 16..=1024).text("Region Size"))
+            .on_hover_text("Width of the regions that each contain at most one structure.");
+        ui.add(egui::Slider::new(&mut structures.chance, 0.0..=1.0).text("Chance"))
+            .on_hover_text("Chance of a region containing a structure.");
+
+        if reset_button(ui) {
+            *structures = StructureOptions::default();
+        }
+    });
+
+    options.sanitize();
+}
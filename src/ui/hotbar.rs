@@ -0,0 +1,100 @@
+use crate::chunk::{registry::VoxelRegistry, Voxel};
+
+/// Size of a voxel slot in the hotbar, in points.
+const SLOT_SIZE: f32 = 36.0;
+
+/// Selector of the voxel placed in the world, shown at the bottom of the screen.
+///
+/// Lists every registered voxel except air. The first nine voxels can also be selected with the
+/// number keys.
+pub struct Hotbar {
+    /// Selectable voxels, along with their names and colors.
+    voxels: Vec<(Voxel, String, egui::Color32)>,
+    /// Index of the selected voxel.
+    selected: usize,
+}
+
+impl Hotbar {
+    pub fn new(registry: &VoxelRegistry) -> Self {
+        let voxels = registry
+            .voxels()
+            .filter(|(voxel, _)| !voxel.is_air())
+            .map(|(voxel, definition)| {
+                let [r, g, b, a] = definition.color;
+                (
+                    voxel,
+                    definition.name.clone(),
+                    egui::Rgba::from_rgba_unmultiplied(r, g, b, a).into(),
+                )
+            })
+            .collect();
+
+        Self {
+            voxels,
+            selected: 0,
+        }
+    }
+
+    /// Returns the selected voxel, or `None` if there are no voxels to select.
+    pub fn selected_voxel(&self) -> Option<Voxel> {
+        self.voxels.get(self.selected).map(|(voxel, ..)| *voxel)
+    }
+
+    /// Selects the voxel at the given index. Indices past the last voxel are ignored.
+    pub fn select(&mut self, index: usize) {
+        if index < self.voxels.len() {
+            self.selected = index;
+        }
+    }
+
+    /// Moves the selection by the given number of slots, wrapping around at either end.
+    pub fn scroll(&mut self, slots: i32) {
+        if !self.voxels.is_empty() {
+            self.selected =
+                (self.selected as i32 + slots).rem_euclid(self.voxels.len() as i32) as usize;
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        egui::Area::new(egui::Id::new("hotbar"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -16.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for (index, (_, name, color)) in self.voxels.iter().enumerate() {
+                            let (rect, response) = ui.allocate_exact_size(
+                                egui::vec2(SLOT_SIZE, SLOT_SIZE),
+                                egui::Sense::click(),
+                            );
+                            if response.on_hover_text(name).clicked() {
+                                self.selected = index;
+                            }
+
+                            let painter = ui.painter();
+                            painter.rect_filled(rect, 4.0, *color);
+                            if index == self.selected {
+                                painter.rect_stroke(
+                                    rect,
+                                    4.0,
+                                    egui::Stroke::new(3.0, egui::Color32::WHITE),
+                                );
+                            }
+                            if index < 9 {
+                                painter.text(
+                                    rect.left_top() + egui::vec2(3.0, 2.0),
+                                    egui::Align2::LEFT_TOP,
+                                    index + 1,
+                                    egui::FontId::proportional(11.0),
+                                    egui::Color32::BLACK,
+                                );
+                            }
+                        }
+                    });
+
+                    if let Some((_, name, _)) = self.voxels.get(self.selected) {
+                        ui.vertical_centered(|ui| ui.label(name));
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::{
+    mpsc::{Receiver, Sender},
+    Arc,
+};
+
+use crate::{
+    chunk::registry::VoxelRegistry,
+    generation::{
+        preview::{Heightmap, HeightmapArea},
+        WorldGenerationOptions,
+    },
+};
+
+/// Number of samples along each axis of the preview.
+const PREVIEW_RESOLUTION: glam::UVec2 = glam::UVec2::splat(128);
+/// Size of the preview image in the UI, in points.
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// Request to sample the heightmap of an area with the given options.
+type PreviewRequest = (WorldGenerationOptions, HeightmapArea);
+
+/// Top-down preview of the terrain generated with the current options.
+///
+/// Heightmaps are sampled on a background thread whenever the options or the previewed area
+/// change. Requests that are superseded before the thread gets to them are skipped.
+pub struct HeightmapPreview {
+    /// Area of the world shown in the preview.
+    area: HeightmapArea,
+    /// Last request sent to the preview thread.
+    requested: Option<PreviewRequest>,
+
+    requests: Sender<PreviewRequest>,
+    results: Receiver<(PreviewRequest, Vec<u8>)>,
+
+    texture: Option<egui::TextureHandle>,
+    /// Request the texture was sampled for.
+    texture_request: Option<PreviewRequest>,
+}
+
+impl HeightmapPreview {
+    /// Creates a preview, and starts its background thread.
+    pub fn new(registry: Arc<VoxelRegistry>) -> Self {
+        let (requests, request_rx) = std::sync::mpsc::channel::<PreviewRequest>();
+        let (result_tx, results) = std::sync::mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("heightmap-preview".to_string())
+            .spawn(move || {
+                while let Ok(mut request) = request_rx.recv() {
+                    while let Ok(newer_request) = request_rx.try_recv() {
+                        request = newer_request;
+                    }
+
+                    let (options, area) = request;
+                    let heightmap = Heightmap::sample(&options, area, &registry);
+                    if result_tx
+                        .send((request, heightmap.colors(&options, &registry)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .expect("to spawn heightmap preview thread");
+
+        Self {
+            area: HeightmapArea {
+                center: glam::DVec2::ZERO,
+                extent: glam::DVec2::splat(2048.0),
+                resolution: PREVIEW_RESOLUTION,
+            },
+            requested: None,
+
+            requests,
+            results,
+
+            texture: None,
+            texture_request: None,
+        }
+    }
+
+    /// Draws the preview and its controls, requesting a new heightmap if the options changed.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        options: &WorldGenerationOptions,
+        camera_position: glam::Vec3,
+    ) {
+        let camera_position = glam::dvec2(camera_position.x as f64, camera_position.z as f64);
+
+        let mut extent = self.area.extent.x;
+        if ui
+            .add(
+                egui::Slider::new(&mut extent, 256.0..=16384.0)
+                    .logarithmic(true)
+                    .text("Area Size"),
+            )
+            .changed()
+        {
+            self.area.extent = glam::DVec2::splat(extent);
+        }
+        ui.horizontal(|ui| {
+            ui.label("Center:");
+            ui.add(egui::DragValue::new(&mut self.area.center.x).prefix("X: "));
+            ui.add(egui::DragValue::new(&mut self.area.center.y).prefix("Z: "));
+            if ui.button("Center on Camera").clicked() {
+                self.area.center = camera_position.round();
+            }
+        });
+
+        let request = (*options, self.area);
+        if self.requested != Some(request) {
+            self.requested = Some(request);
+            self.requests
+                .send(request)
+                .expect("heightmap preview thread to be running");
+        }
+
+        while let Ok((request, colors)) = self.results.try_recv() {
+            let size = request.1.resolution.to_array().map(|size| size as usize);
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, &colors);
+            self.texture = Some(ui.ctx().load_texture(
+                "heightmap-preview",
+                image,
+                egui::TextureOptions::NEAREST,
+            ));
+            self.texture_request = Some(request);
+        }
+
+        let (Some(texture), Some((_, texture_area))) = (&self.texture, self.texture_request) else {
+            ui.spinner();
+            return;
+        };
+
+        let response = ui.image(egui::load::SizedTexture::new(
+            texture.id(),
+            egui::Vec2::splat(PREVIEW_SIZE),
+        ));
+
+        if let Some(position) = texture_area.normalized_position(camera_position) {
+            let marker = response.rect.min
+                + egui::vec2(position.x as f32, position.y as f32) * response.rect.size();
+            ui.painter().circle(
+                marker,
+                4.0,
+                egui::Color32::RED,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
+        }
+
+        if self.texture_request != self.requested {
+            ui.label("Updating preview...");
+        }
+    }
+}
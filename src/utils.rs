@@ -3,3 +3,69 @@ pub fn coord_to_index(position: glam::UVec3, size: glam::UVec3) -> usize {
         + position.y as usize * size.x as usize
         + position.z as usize * size.x as usize * size.y as usize
 }
+
+/// Splits a position in the world into the grid position of the chunk containing it, and the
+/// position within that chunk.
+pub fn world_to_chunk(
+    position: glam::IVec3,
+    chunk_size: glam::UVec3,
+) -> (glam::IVec3, glam::UVec3) {
+    let chunk_size = chunk_size.as_ivec3();
+    (
+        position.div_euclid(chunk_size),
+        position.rem_euclid(chunk_size).as_uvec3(),
+    )
+}
+
+/// Returns the grid positions of the chunks containing the voxel at the given position, or any
+/// voxel sharing a face, an edge or a corner with it.
+///
+/// These are the chunks whose meshes depend on the voxel.
+pub fn chunks_around_voxel(
+    position: glam::IVec3,
+    chunk_size: glam::UVec3,
+) -> impl Iterator<Item = glam::IVec3> {
+    let min = (position - 1).div_euclid(chunk_size.as_ivec3());
+    let max = (position + 1).div_euclid(chunk_size.as_ivec3());
+
+    (min.x..=max.x).flat_map(move |x| {
+        (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| glam::ivec3(x, y, z)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_positions_belong_to_negative_chunks() {
+        let size = glam::UVec3::splat(16);
+
+        assert_eq!(
+            world_to_chunk(glam::ivec3(-1, 0, 17), size),
+            (glam::ivec3(-1, 0, 1), glam::uvec3(15, 0, 1))
+        );
+        assert_eq!(
+            world_to_chunk(glam::ivec3(-16, -17, 15), size),
+            (glam::ivec3(-1, -2, 0), glam::uvec3(0, 15, 15))
+        );
+    }
+
+    #[test]
+    fn voxels_on_chunk_borders_touch_neighbouring_chunks() {
+        let size = glam::UVec3::splat(16);
+        let chunks = |position| chunks_around_voxel(position, size).collect::<Vec<_>>();
+
+        assert_eq!(chunks(glam::ivec3(5, 5, 5)), [glam::IVec3::ZERO]);
+        assert_eq!(
+            chunks(glam::ivec3(0, 5, 15)),
+            [
+                glam::ivec3(-1, 0, 0),
+                glam::ivec3(-1, 0, 1),
+                glam::ivec3(0, 0, 0),
+                glam::ivec3(0, 0, 1),
+            ]
+        );
+        assert_eq!(chunks(glam::ivec3(-1, -1, -1)).len(), 8);
+    }
+}
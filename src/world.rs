@@ -1,43 +1,130 @@
 use std::{
     collections::{HashMap, HashSet},
+    io,
+    path::PathBuf,
     rc::Rc,
-    sync::mpsc::{Receiver, Sender},
-    thread,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use glium::{DrawParameters, Surface};
+use jobs::{Epoch, JobQueue, JobResult};
+use raycast::RaycastHit;
+use region::RegionStore;
 
 use crate::{
     app::Window,
     chunk::{
-        mesh::{Axis, Direction, Mesh, Vertex},
-        Chunk, VoxelUniforms,
+        border::ChunkBorder,
+        light,
+        mesh::{Mesh, Vertex},
+        registry::VoxelRegistry,
+        Chunk, Mesher, Voxel, VoxelUniforms,
     },
-    generation::WorldGenerationOptions,
+    generation::{features::PendingWrites, structures::StructureLibrary, WorldGenerationOptions},
     transform::{Matrix3x3, Matrix4x4},
+    utils::{chunks_around_voxel, world_to_chunk},
 };
 
+pub mod brush;
+mod jobs;
+pub mod raycast;
+mod region;
+
+/// Number of chunks beyond the render distance at which chunks are unloaded.
+///
+/// Keeping chunks slightly outside the render distance loaded avoids regenerating them when the
+/// camera moves back and forth across a chunk border.
+const UNLOAD_MARGIN: i32 = 1;
+
+/// Default memory budget for loaded chunks and their meshes, in bytes.
+pub const DEFAULT_MEMORY_BUDGET: usize = 512 * 1024 * 1024;
+
+/// Default time spent integrating finished chunks and meshes every frame.
+pub const DEFAULT_FRAME_BUDGET: Duration = Duration::from_millis(4);
+
+type ChunkBuffers = (glium::VertexBuffer<Vertex>, glium::IndexBuffer<u32>);
+
 struct Channel<T> {
-    tx: Sender<T>,
-    rx: Receiver<T>,
+    tx: Sender<JobResult<T>>,
+    rx: Receiver<JobResult<T>>,
     in_process: HashSet<glam::IVec3>,
 }
 
+/// Statistics about the chunks loaded in the world.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorldStats {
+    /// Number of chunks that are loaded.
+    pub loaded_chunks: usize,
+    /// Number of loaded chunks that have a mesh.
+    pub meshed_chunks: usize,
+    /// Number of chunks waiting to be generated.
+    pub queued_generation_jobs: usize,
+    /// Number of chunks waiting to be meshed.
+    pub queued_meshing_jobs: usize,
+    /// Number of jobs running on the worker pool.
+    pub jobs_in_flight: usize,
+    /// Estimated memory used by the loaded chunks and their meshes, in bytes.
+    pub memory_usage: usize,
+    /// Memory budget for the loaded chunks and their meshes, in bytes.
+    pub memory_budget: usize,
+    /// Number of generated chunks integrated during the last update.
+    pub integrated_chunks: usize,
+    /// Number of chunk meshes uploaded to the GPU during the last update.
+    pub uploaded_meshes: usize,
+}
+
 /// Represents the world.
 pub struct World {
     render_distance: u8,
+    /// Memory budget for loaded chunks and their meshes, in bytes.
+    memory_budget: usize,
+    /// Time spent integrating finished chunks and meshes every frame.
+    frame_budget: Duration,
+    /// Registry of the voxel types in the world.
+    registry: Arc<VoxelRegistry>,
+    /// Structures placed in the world while it is generated.
+    structures: Arc<StructureLibrary>,
+    /// Algorithm used to mesh chunks.
+    mesher: Mesher,
+    /// Size of the chunks, from the generation options of the last update.
+    chunk_size: glam::UVec3,
     /// Chunks in the world that have been generated.
     chunks: HashMap<glam::IVec3, Chunk>,
-
-    chunk_generator_channel: Channel<Chunk>,
-    chunk_meshing_channel: Channel<(glam::IVec3, Option<Mesh>, Option<Mesh>)>,
+    /// Region files chunks are saved to and loaded from, if the world is saved.
+    region_store: Option<Arc<RegionStore>>,
+    /// Voxels of the features the loaded chunks placed outside of themselves, see [`insert_chunk`].
+    pending_writes: PendingWrites,
+
+    /// Pool of worker threads that generate and mesh chunks.
+    worker_pool: rayon::ThreadPool,
+    /// Maximum number of jobs that can be dispatched to the worker pool at once.
+    max_jobs_in_flight: usize,
+    /// Grid position of the chunk containing the camera, used to prioritise jobs.
+    center_chunk_position: glam::IVec3,
+    /// Chunks waiting to be generated.
+    generation_queue: JobQueue,
+    /// Chunks waiting to be meshed.
+    meshing_queue: JobQueue,
+    /// Current generation of the world, advanced every time the world is cleared.
+    epoch: Epoch,
+    /// Number of jobs from previous epochs that are still running on the worker pool.
+    stale_jobs_in_flight: usize,
+    /// Number of generated chunks integrated during the last update.
+    integrated_chunks: usize,
+    /// Number of chunk meshes uploaded to the GPU during the last update.
+    uploaded_meshes: usize,
+
+    chunk_generator_channel: Channel<(Chunk, PendingWrites)>,
+    chunk_meshing_channel: Channel<(Option<Mesh>, Option<Mesh>)>,
 
     /// Meshes for solid voxels of a chunk.
-    chunk_solid_meshes:
-        HashMap<glam::IVec3, (glium::VertexBuffer<Vertex>, glium::IndexBuffer<u32>)>,
+    chunk_solid_meshes: HashMap<glam::IVec3, ChunkBuffers>,
     /// Meshes for transparent voxels of a chunk.
-    chunk_transparent_meshes:
-        HashMap<glam::IVec3, (glium::VertexBuffer<Vertex>, glium::IndexBuffer<u32>)>,
+    chunk_transparent_meshes: HashMap<glam::IVec3, ChunkBuffers>,
     /// Uniforms for a chunk.
     chunk_uniforms: HashMap<glam::IVec3, (Matrix4x4, Matrix3x3)>,
 
@@ -46,24 +133,56 @@ pub struct World {
 
 impl World {
     /// Creates a new empty world.
-    pub fn new(window: Rc<Window>, render_distance: u8) -> Self {
+    pub fn new(
+        window: Rc<Window>,
+        render_distance: u8,
+        registry: Arc<VoxelRegistry>,
+        structures: Arc<StructureLibrary>,
+    ) -> Self {
         let (tx, rx) = std::sync::mpsc::channel();
-        let chunk_generator_channel = Channel::<Chunk> {
+        let chunk_generator_channel = Channel::<(Chunk, PendingWrites)> {
             tx,
             rx,
             in_process: HashSet::new(),
         };
 
         let (tx, rx) = std::sync::mpsc::channel();
-        let chunk_meshing_channel = Channel::<(glam::IVec3, Option<Mesh>, Option<Mesh>)> {
+        let chunk_meshing_channel = Channel::<(Option<Mesh>, Option<Mesh>)> {
             tx,
             rx,
             in_process: HashSet::new(),
         };
 
+        let worker_count = std::thread::available_parallelism()
+            .map_or(1, |count| count.get().saturating_sub(1))
+            .max(1);
+        let worker_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(worker_count)
+            .thread_name(|index| format!("chunk-worker-{index}"))
+            .build()
+            .expect("to create chunk worker pool");
+
         Self {
             render_distance,
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            frame_budget: DEFAULT_FRAME_BUDGET,
+            registry,
+            structures,
+            mesher: Mesher::default(),
+            chunk_size: WorldGenerationOptions::default().chunk_size,
             chunks: HashMap::new(),
+            region_store: None,
+            pending_writes: PendingWrites::default(),
+
+            worker_pool,
+            max_jobs_in_flight: worker_count * 2,
+            center_chunk_position: glam::IVec3::ZERO,
+            generation_queue: JobQueue::new(),
+            meshing_queue: JobQueue::new(),
+            epoch: Epoch::default(),
+            stale_jobs_in_flight: 0,
+            integrated_chunks: 0,
+            uploaded_meshes: 0,
 
             chunk_generator_channel,
             chunk_meshing_channel,
@@ -76,9 +195,170 @@ impl World {
         }
     }
 
+    /// Returns the algorithm used to mesh chunks.
+    pub fn mesher(&self) -> Mesher {
+        self.mesher
+    }
+
+    /// Sets the algorithm used to mesh chunks.
+    ///
+    /// Only affects chunks that are meshed after the change.
+    pub fn set_mesher(&mut self, mesher: Mesher) {
+        self.mesher = mesher;
+    }
+
+    /// Sets the directory the world is saved to.
+    ///
+    /// Chunks saved in the directory are loaded instead of being generated, and modified chunks are
+    /// saved to it when they are unloaded. Call [`World::save`] before changing the directory to
+    /// keep the modifications to the current world.
+    pub fn set_save_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.region_store = Some(Arc::new(RegionStore::new(directory)));
+    }
+
+    /// Saves every modified chunk to the save directory.
+    ///
+    /// Does nothing if no save directory is set.
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(region_store) = &self.region_store else {
+            return Ok(());
+        };
+
+        for chunk in self.chunks.values_mut().filter(|chunk| chunk.is_dirty()) {
+            region_store.save_chunk(chunk, &self.pending_writes, &self.registry)?;
+            chunk.set_dirty(false);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the memory budget for loaded chunks and their meshes, in bytes.
+    ///
+    /// When the budget is exceeded, the farthest chunks outside the render distance are unloaded
+    /// early. Chunks within the render distance are never unloaded, so the budget may still be
+    /// exceeded if it is too small for the render distance.
+    pub fn set_memory_budget(&mut self, memory_budget: usize) {
+        self.memory_budget = memory_budget;
+    }
+
+    /// Sets the time spent integrating finished chunks and meshes every frame.
+    pub fn set_frame_budget(&mut self, frame_budget: Duration) {
+        self.frame_budget = frame_budget;
+    }
+
+    /// Returns statistics about the chunks loaded in the world.
+    pub fn stats(&self) -> WorldStats {
+        WorldStats {
+            loaded_chunks: self.chunks.len(),
+            meshed_chunks: self.chunk_uniforms.len(),
+            queued_generation_jobs: self.generation_queue.len(),
+            queued_meshing_jobs: self.meshing_queue.len(),
+            jobs_in_flight: self.jobs_in_flight(),
+            memory_usage: self.memory_usage(),
+            memory_budget: self.memory_budget,
+            integrated_chunks: self.integrated_chunks,
+            uploaded_meshes: self.uploaded_meshes,
+        }
+    }
+
+    /// Returns the voxel at the given position in the world.
+    ///
+    /// Returns `None` if the chunk containing the position is not loaded.
+    pub fn get_voxel(&self, position: glam::IVec3) -> Option<Voxel> {
+        let (grid_position, local_position) = world_to_chunk(position, self.chunk_size);
+        self.chunks
+            .get(&grid_position)?
+            .get_voxel(local_position)
+            .copied()
+    }
+
+    /// Casts a ray through the loaded chunks, and returns the first voxel accepted by `filter`.
+    ///
+    /// Returns `None` if no voxel is hit within `max_distance`, or the ray leaves the loaded
+    /// chunks first. See [`raycast::raycast`].
+    pub fn raycast(
+        &self,
+        origin: glam::Vec3,
+        direction: glam::Vec3,
+        max_distance: f32,
+        filter: impl Fn(Voxel) -> bool,
+    ) -> Option<RaycastHit> {
+        raycast::raycast(
+            origin,
+            direction,
+            max_distance,
+            |position| self.get_voxel(position),
+            filter,
+        )
+    }
+
+    /// Sets the voxel at the given position in the world.
+    ///
+    /// The chunk containing the position is marked as modified, and the light around the voxel is
+    /// updated. The chunk is queued for meshing, along with the neighbouring chunks whose faces,
+    /// ambient occlusion or light depend on the voxel.
+    ///
+    /// Returns `false` if the chunk containing the position is not loaded.
+    pub fn set_voxel(&mut self, position: glam::IVec3, voxel: Voxel) -> bool {
+        let (grid_position, _) = world_to_chunk(position, self.chunk_size);
+        if !self.chunks.contains_key(&grid_position) {
+            return false;
+        }
+
+        self.set_voxels([(position, voxel)]);
+        true
+    }
+
+    /// Sets many voxels in the world at once, as if by [`World::set_voxel`].
+    ///
+    /// The light is updated for all the voxels together, and every affected chunk is queued for
+    /// meshing once. Voxels in chunks that are not loaded are skipped.
+    ///
+    /// Returns the number of voxels that changed.
+    pub fn set_voxels(&mut self, voxels: impl IntoIterator<Item = (glam::IVec3, Voxel)>) -> usize {
+        let mut changed = Vec::new();
+        for (position, voxel) in voxels {
+            let (grid_position, local_position) = world_to_chunk(position, self.chunk_size);
+            let Some(chunk) = self.chunks.get_mut(&grid_position) else {
+                continue;
+            };
+
+            if chunk.get_voxel(local_position) != Some(&voxel) {
+                chunk.set_voxel(local_position, voxel);
+                chunk.set_dirty(true);
+                changed.push(position);
+            }
+        }
+
+        let mut remesh = light::relight(&mut self.chunks, &self.registry, changed.iter().copied());
+        remesh.extend(
+            changed
+                .iter()
+                .flat_map(|position| chunks_around_voxel(*position, self.chunk_size)),
+        );
+        for grid_position in remesh {
+            self.queue_meshing(grid_position);
+        }
+
+        changed.len()
+    }
+
     /// Clears the world.
+    ///
+    /// Jobs that are still queued or running for the cleared world are cancelled, and their results
+    /// are discarded.
     pub fn clear(&mut self) {
+        self.epoch.advance();
+        self.stale_jobs_in_flight += self.chunk_generator_channel.in_process.len()
+            + self.chunk_meshing_channel.in_process.len();
+        self.chunk_generator_channel.in_process.clear();
+        self.chunk_meshing_channel.in_process.clear();
+
+        self.generation_queue.clear();
+        self.meshing_queue.clear();
+
         self.chunks.clear();
+        self.pending_writes.clear();
         self.chunk_solid_meshes.clear();
         self.chunk_transparent_meshes.clear();
         self.chunk_uniforms.clear();
@@ -86,16 +366,29 @@ impl World {
 
     /// Updates the world.
     ///
-    /// This generates new chunks around the camera position.
+    /// This queues new chunks around the camera position for generation, integrates as many
+    /// finished chunks and meshes as fit into the frame budget, and dispatches the most urgent
+    /// queued jobs to the worker pool.
     pub fn update(
         &mut self,
         camera_position: glam::Vec3,
         generation_options: &WorldGenerationOptions,
     ) {
+        self.chunk_size = generation_options.chunk_size;
         let center_chunk_pos = (camera_position / generation_options.chunk_size.as_vec3())
             .floor()
             .as_ivec3();
 
+        if center_chunk_pos != self.center_chunk_position {
+            self.center_chunk_position = center_chunk_pos;
+            self.generation_queue
+                .reprioritise(|position| position.distance_squared(center_chunk_pos));
+            self.meshing_queue
+                .reprioritise(|position| position.distance_squared(center_chunk_pos));
+
+            self.unload_distant_chunks();
+        }
+
         for x in (center_chunk_pos.x - self.render_distance as i32)
             ..=(center_chunk_pos.x + self.render_distance as i32)
         {
@@ -106,77 +399,379 @@ impl World {
                     ..=(center_chunk_pos.z + self.render_distance as i32)
                 {
                     let chunk_pos = glam::IVec3::new(x, y, z);
-                    if self.chunks.get(&chunk_pos).is_none()
+                    if !self.chunks.contains_key(&chunk_pos)
                         && !self.chunk_generator_channel.in_process.contains(&chunk_pos)
                     {
-                        self.chunk_generator_channel.in_process.insert(chunk_pos);
-
-                        let tx = self.chunk_generator_channel.tx.clone();
-                        let generation_options = generation_options.clone();
-                        thread::spawn(move || {
-                            let chunk =
-                                crate::generation::generate_chunk(generation_options, chunk_pos);
-                            tx.send(chunk)
-                                .expect("to send generated chunk back to main thread");
-                        });
+                        self.generation_queue
+                            .push(chunk_pos, chunk_pos.distance_squared(center_chunk_pos));
                     }
                 }
             }
         }
 
-        if let Ok(chunk) = self.chunk_generator_channel.rx.try_recv() {
-            self.chunk_generator_channel
-                .in_process
-                .remove(&chunk.grid_position);
+        self.integrate_job_results();
+        self.dispatch_jobs(generation_options);
+    }
 
-            if !chunk.is_empty() {
-                self.mesh_chunk(&chunk);
+    /// Integrates finished chunks and meshes until the frame budget is used up.
+    ///
+    /// Meshes are integrated before chunks, as they make already generated chunks visible. At least
+    /// one result is integrated per frame, however small the budget.
+    fn integrate_job_results(&mut self) {
+        let deadline = Instant::now() + self.frame_budget;
+        self.integrated_chunks = 0;
+        self.uploaded_meshes = 0;
+
+        loop {
+            if let Ok(result) = self.chunk_meshing_channel.rx.try_recv() {
+                let grid_position = result.grid_position;
+                if let Some((solid_mesh, transparent_mesh)) = self
+                    .accept_job_result(result, |world| &mut world.chunk_meshing_channel.in_process)
+                {
+                    if self
+                        .chunks
+                        .get(&grid_position)
+                        .is_some_and(|chunk| !chunk.is_empty())
+                    {
+                        self.prepare_chunk_for_rendering(
+                            grid_position,
+                            solid_mesh,
+                            transparent_mesh,
+                        );
+                        self.uploaded_meshes += 1;
+                    }
+                }
+            } else if let Ok(result) = self.chunk_generator_channel.rx.try_recv() {
+                if let Some((chunk, pending_writes)) = self.accept_job_result(result, |world| {
+                    &mut world.chunk_generator_channel.in_process
+                }) {
+                    if self.integrate_chunk(chunk, pending_writes) {
+                        self.integrated_chunks += 1;
+                    }
+                }
+            } else {
+                break;
             }
 
-            // Re-mesh neighbouring chunks
-            let neighbours = self.get_neigbour_chunks(chunk.grid_position);
-            for neighbour in neighbours.values() {
-                self.mesh_chunk(neighbour);
+            if Instant::now() >= deadline {
+                break;
             }
+        }
 
-            self.chunks.insert(chunk.grid_position, chunk);
+        if self.integrated_chunks > 0 || self.uploaded_meshes > 0 {
+            self.enforce_memory_budget();
         }
+    }
 
-        if let Ok((grid_position, solid_mesh, transparent_mesh)) =
-            self.chunk_meshing_channel.rx.try_recv()
-        {
-            self.prepare_chunk_for_rendering(grid_position, solid_mesh, transparent_mesh);
+    /// Inserts a newly generated chunk into the world, and queues it and its neighbours for meshing.
+    ///
+    /// Features are exchanged with the loaded chunks around it, see [`insert_chunk`]. Receiving
+    /// features doesn't mark a chunk as modified: unmodified chunks are generated again when they
+    /// are loaded, and receive the features of their neighbours again.
+    ///
+    /// The light of the new chunk is merged with the light of its neighbours, and every chunk whose
+    /// light changed is queued for meshing as well.
+    ///
+    /// Returns `false` if the chunk was discarded because the camera moved away from it while it
+    /// was being generated. Its features are discarded as well, as they are placed again once it
+    /// is generated again.
+    fn integrate_chunk(&mut self, chunk: Chunk, pending_writes: PendingWrites) -> bool {
+        let grid_position = chunk.grid_position;
+        if self.distance_to_center(grid_position) > self.unload_distance() {
+            return false;
+        }
+
+        let (written, changed_chunks) = insert_chunk(
+            &mut self.chunks,
+            &mut self.pending_writes,
+            chunk,
+            pending_writes,
+        );
+        self.queue_meshing_around(grid_position);
+        for changed_chunk in changed_chunks {
+            self.queue_meshing_around(changed_chunk);
+        }
+
+        let mut relit = light::light_borders(&mut self.chunks, &self.registry, grid_position);
+        relit.extend(light::relight(&mut self.chunks, &self.registry, written));
+        for grid_position in relit {
+            self.queue_meshing(grid_position);
         }
+
+        true
     }
 
-    fn get_neigbour_chunks(&self, chunk_position: glam::IVec3) -> HashMap<glam::IVec3, Chunk> {
-        let mut neighbours = HashMap::new();
+    /// Queues the chunk at the given position for meshing, along with the neighbouring chunks whose
+    /// faces, ambient occlusion and light depend on it.
+    fn queue_meshing_around(&mut self, grid_position: glam::IVec3) {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    self.queue_meshing(grid_position + glam::ivec3(x, y, z));
+                }
+            }
+        }
+    }
 
-        for axis in [Axis::X, Axis::Y, Axis::Z] {
-            for direction in [Direction::Positive, Direction::Negative] {
-                let neighbour_position = chunk_position + axis.get_normal(direction).as_ivec3();
+    /// Returns the distance, in chunks, from the chunk containing the camera to the given chunk.
+    ///
+    /// Chunks are loaded in a cube around the camera, so this is the largest distance along any
+    /// axis.
+    fn distance_to_center(&self, grid_position: glam::IVec3) -> i32 {
+        (grid_position - self.center_chunk_position)
+            .abs()
+            .max_element()
+    }
+
+    /// Returns the distance, in chunks, beyond which chunks are unloaded.
+    fn unload_distance(&self) -> i32 {
+        self.render_distance as i32 + UNLOAD_MARGIN
+    }
+
+    /// Unloads every chunk beyond the unload distance, and drops their queued jobs.
+    fn unload_distant_chunks(&mut self) {
+        let unload_distance = self.unload_distance();
+        let distant_chunks = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|grid_position| self.distance_to_center(*grid_position) > unload_distance)
+            .collect::<Vec<_>>();
+        for grid_position in distant_chunks {
+            self.unload_chunk(grid_position);
+        }
 
-                if let Some(neighbour) = self.chunks.get(&neighbour_position) {
-                    neighbours.insert(neighbour_position, (*neighbour).clone());
+        let center_chunk_position = self.center_chunk_position;
+        let is_near = |grid_position: glam::IVec3| {
+            (grid_position - center_chunk_position).abs().max_element() <= unload_distance
+        };
+        self.generation_queue.retain(is_near);
+        self.meshing_queue.retain(is_near);
+    }
+
+    /// Unloads the farthest chunks outside the render distance until the memory budget is met.
+    fn enforce_memory_budget(&mut self) {
+        let mut memory_usage = self.memory_usage();
+        if memory_usage <= self.memory_budget {
+            return;
+        }
+
+        let render_distance = self.render_distance as i32;
+        let mut candidates = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|grid_position| self.distance_to_center(*grid_position) > render_distance)
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|grid_position| {
+            std::cmp::Reverse(grid_position.distance_squared(self.center_chunk_position))
+        });
+
+        for grid_position in candidates {
+            if memory_usage <= self.memory_budget {
+                break;
+            }
+
+            memory_usage -= self.chunk_memory_usage(grid_position);
+            self.unload_chunk(grid_position);
+        }
+    }
+
+    /// Returns an estimate of the memory used by the loaded chunks, their meshes and the pending
+    /// writes of their features, in bytes.
+    fn memory_usage(&self) -> usize {
+        self.chunks
+            .keys()
+            .map(|grid_position| self.chunk_memory_usage(*grid_position))
+            .sum::<usize>()
+            + self.pending_writes.memory_usage()
+    }
+
+    /// Returns an estimate of the memory used by the chunk at the given position and its meshes,
+    /// in bytes.
+    fn chunk_memory_usage(&self, grid_position: glam::IVec3) -> usize {
+        let buffers_size = |(vertex_buffer, index_buffer): &ChunkBuffers| {
+            vertex_buffer.get_size() + index_buffer.get_size()
+        };
+
+        self.chunks
+            .get(&grid_position)
+            .map_or(0, |chunk| chunk.memory_usage())
+            + self
+                .chunk_solid_meshes
+                .get(&grid_position)
+                .map_or(0, buffers_size)
+            + self
+                .chunk_transparent_meshes
+                .get(&grid_position)
+                .map_or(0, buffers_size)
+    }
+
+    /// Removes the chunk at the given position and its meshes from the world, saving the chunk
+    /// first if it was modified.
+    ///
+    /// The writes of its features are dropped as well, as they are placed again once it is loaded
+    /// again. The writes other chunks placed into it are kept, so that it receives them again if it is
+    /// loaded again while they are still loaded.
+    fn unload_chunk(&mut self, grid_position: glam::IVec3) {
+        if let (Some(chunk), Some(region_store)) =
+            (self.chunks.remove(&grid_position), &self.region_store)
+        {
+            if chunk.is_dirty() {
+                if let Err(error) =
+                    region_store.save_chunk(&chunk, &self.pending_writes, &self.registry)
+                {
+                    eprintln!("Failed to save chunk {grid_position}: {error}");
                 }
             }
         }
+        self.pending_writes.remove_source(grid_position);
+
+        self.chunk_solid_meshes.remove(&grid_position);
+        self.chunk_transparent_meshes.remove(&grid_position);
+        self.chunk_uniforms.remove(&grid_position);
+    }
+
+    /// Marks the job that produced the result as finished, returning its output.
+    ///
+    /// Results of jobs dispatched before the world was last cleared are discarded.
+    fn accept_job_result<T>(
+        &mut self,
+        result: JobResult<T>,
+        in_process: impl FnOnce(&mut Self) -> &mut HashSet<glam::IVec3>,
+    ) -> Option<T> {
+        if result.epoch != self.epoch.current() {
+            self.stale_jobs_in_flight -= 1;
+            return None;
+        }
 
-        neighbours
+        in_process(self).remove(&result.grid_position);
+        result.output
     }
 
-    fn mesh_chunk(&mut self, chunk: &Chunk) {
-        let neighbours = self.get_neigbour_chunks(chunk.grid_position);
+    /// Queues the chunk at the given position to be meshed.
+    ///
+    /// Chunks that are not loaded are ignored. Entirely empty chunks have nothing to mesh, and only
+    /// lose the meshes they had before they were emptied.
+    fn queue_meshing(&mut self, grid_position: glam::IVec3) {
+        let Some(chunk) = self.chunks.get(&grid_position) else {
+            return;
+        };
 
-        let tx = self.chunk_meshing_channel.tx.clone();
-        let chunk = chunk.clone();
-        thread::spawn(move || {
-            let (solid_mesh, transparent_mesh) = chunk.mesh(&neighbours);
-            tx.send((chunk.grid_position, solid_mesh, transparent_mesh))
-                .expect("to send generated mesh back to main thread");
+        if chunk.is_empty() {
+            self.chunk_solid_meshes.remove(&grid_position);
+            self.chunk_transparent_meshes.remove(&grid_position);
+            self.chunk_uniforms.remove(&grid_position);
+        } else {
+            self.meshing_queue.push(
+                grid_position,
+                grid_position.distance_squared(self.center_chunk_position),
+            );
+        }
+    }
+
+    /// Returns the number of jobs running on the worker pool, including stale ones.
+    fn jobs_in_flight(&self) -> usize {
+        self.stale_jobs_in_flight
+            + self.chunk_generator_channel.in_process.len()
+            + self.chunk_meshing_channel.in_process.len()
+    }
+
+    /// Dispatches queued jobs to the worker pool, until the maximum number of jobs are in flight.
+    ///
+    /// Meshing jobs are dispatched first, as they make already generated chunks visible.
+    fn dispatch_jobs(&mut self, generation_options: &WorldGenerationOptions) {
+        // A chunk that is already being meshed is meshed again once the current job finishes, so
+        // that an older mesh never replaces a newer one
+        let mut deferred = Vec::new();
+        while self.jobs_in_flight() < self.max_jobs_in_flight {
+            let Some(grid_position) = self.meshing_queue.pop() else {
+                break;
+            };
+
+            if self
+                .chunk_meshing_channel
+                .in_process
+                .contains(&grid_position)
+            {
+                deferred.push(grid_position);
+            } else if let Some(chunk) = self.chunks.get(&grid_position) {
+                self.mesh_chunk(chunk.clone());
+            }
+        }
+        for grid_position in deferred {
+            self.queue_meshing(grid_position);
+        }
+
+        while self.jobs_in_flight() < self.max_jobs_in_flight {
+            let Some(chunk_pos) = self.generation_queue.pop() else {
+                break;
+            };
+
+            if self.chunks.contains_key(&chunk_pos) {
+                continue;
+            }
+
+            self.chunk_generator_channel.in_process.insert(chunk_pos);
+
+            let generation_options = *generation_options;
+            let registry = self.registry.clone();
+            let structures = self.structures.clone();
+            let region_store = self.region_store.clone();
+            self.spawn_job(
+                self.chunk_generator_channel.tx.clone(),
+                chunk_pos,
+                move || {
+                    load_or_generate_chunk(
+                        region_store.as_deref(),
+                        generation_options,
+                        chunk_pos,
+                        &registry,
+                        &structures,
+                    )
+                },
+            );
+        }
+    }
+
+    /// Runs a job for the given chunk on the worker pool, sending its result through `tx`.
+    ///
+    /// The job is skipped if the world is cleared before it starts running.
+    fn spawn_job<T: Send + 'static>(
+        &self,
+        tx: Sender<JobResult<T>>,
+        grid_position: glam::IVec3,
+        job: impl FnOnce() -> T + Send + 'static,
+    ) {
+        let epoch = self.epoch.clone();
+        let dispatched_epoch = epoch.current();
+        self.worker_pool.spawn(move || {
+            let output = (epoch.current() == dispatched_epoch).then(job);
+
+            // The world may have been dropped while the job was running
+            let _ = tx.send(JobResult {
+                epoch: dispatched_epoch,
+                grid_position,
+                output,
+            });
         });
     }
 
+    fn mesh_chunk(&mut self, chunk: Chunk) {
+        let border = ChunkBorder::new(&chunk, &self.chunks);
+        self.chunk_meshing_channel
+            .in_process
+            .insert(chunk.grid_position);
+
+        let registry = self.registry.clone();
+        let mesher = self.mesher;
+        self.spawn_job(
+            self.chunk_meshing_channel.tx.clone(),
+            chunk.grid_position,
+            move || chunk.mesh_with(mesher, &border, &registry),
+        );
+    }
+
     fn prepare_chunk_for_rendering(
         &mut self,
         grid_position: glam::IVec3,
@@ -192,6 +787,8 @@ impl World {
                 .expect("to create index buffer");
             self.chunk_solid_meshes
                 .insert(grid_position, (vertex_buffer, index_buffer));
+        } else {
+            self.chunk_solid_meshes.remove(&grid_position);
         }
 
         if let Some(transparent_mesh) = transparent_mesh {
@@ -203,6 +800,8 @@ impl World {
                 .expect("to create index buffer");
             self.chunk_transparent_meshes
                 .insert(grid_position, (vertex_buffer, index_buffer));
+        } else {
+            self.chunk_transparent_meshes.remove(&grid_position);
         }
 
         let chunk = self.chunks.get(&grid_position).unwrap();
@@ -230,13 +829,13 @@ impl World {
                 .draw(
                     vertex_buffer,
                     index_buffer,
-                    &shader,
+                    shader,
                     &uniform! {
                         view_proj: uniforms.view_projection,
                         model: *model,
                         normal_matrix: *normal,
                         light_color: uniforms.light_color,
-                        light_position: uniforms.light_position
+                        light_direction: uniforms.light_direction
                     },
                     &DrawParameters {
                         polygon_mode: if draw_wireframe {
@@ -265,13 +864,13 @@ impl World {
                 .draw(
                     vertex_buffer,
                     index_buffer,
-                    &shader,
+                    shader,
                     &uniform! {
                         view_proj: uniforms.view_projection,
                         model: *model,
                         normal_matrix: *normal,
                         light_color: uniforms.light_color,
-                        light_position: uniforms.light_position
+                        light_direction: uniforms.light_direction
                     },
                     &DrawParameters {
                         polygon_mode: if draw_wireframe {
@@ -294,3 +893,124 @@ impl World {
         }
     }
 }
+
+/// Inserts `chunk` into `chunks`, exchanging features with the chunks already loaded.
+///
+/// The features other chunks placed into the new chunk are written into it, and the features it
+/// placed into other chunks, `placed_writes`, are written into the loaded ones. Its writes are then
+/// kept in `pending_writes` along with the others, so that chunks loaded later, or unloaded and
+/// loaded again, receive them as well.
+///
+/// Returns the positions in the world of the voxels written by features, along with the grid
+/// positions of the loaded chunks, other than the new one, that received any of them.
+fn insert_chunk(
+    chunks: &mut HashMap<glam::IVec3, Chunk>,
+    pending_writes: &mut PendingWrites,
+    mut chunk: Chunk,
+    placed_writes: PendingWrites,
+) -> (Vec<glam::IVec3>, Vec<glam::IVec3>) {
+    let grid_position = chunk.grid_position;
+    let chunk_size = chunk.size().as_ivec3();
+    let mut written = pending_writes
+        .apply(&mut chunk)
+        .into_iter()
+        .map(|position| grid_position * chunk_size + position.as_ivec3())
+        .collect::<Vec<_>>();
+    chunks.insert(grid_position, chunk);
+
+    let mut changed_chunks = Vec::new();
+    for target in placed_writes.grid_positions() {
+        let Some(chunk) = chunks.get_mut(&target) else {
+            continue;
+        };
+
+        let changed = placed_writes.apply(chunk);
+        if !changed.is_empty() {
+            written.extend(
+                changed
+                    .into_iter()
+                    .map(|position| target * chunk_size + position.as_ivec3()),
+            );
+            changed_chunks.push(target);
+        }
+    }
+    pending_writes.append(placed_writes);
+
+    (written, changed_chunks)
+}
+
+/// Loads the chunk at the given grid position from the region files, generating it if it was never
+/// saved, and lights it on its own.
+///
+/// Returns the chunk, along with the voxels of the features it places into neighbouring chunks.
+/// Saved chunks are stored along with the voxels of their features, so they are never generated.
+fn load_or_generate_chunk(
+    region_store: Option<&RegionStore>,
+    generation_options: WorldGenerationOptions,
+    grid_position: glam::IVec3,
+    registry: &VoxelRegistry,
+    structures: &StructureLibrary,
+) -> (Chunk, PendingWrites) {
+    let loaded = region_store.and_then(|region_store| {
+        region_store
+            .load_chunk(grid_position, generation_options.chunk_size, registry)
+            .unwrap_or_else(|error| {
+                eprintln!("Failed to load chunk {grid_position}: {error}");
+                None
+            })
+    });
+
+    let (mut chunk, pending_writes) = loaded.unwrap_or_else(|| {
+        crate::generation::generate_chunk(generation_options, grid_position, registry, structures)
+    });
+    light::light_chunk(&mut chunk, registry);
+
+    (chunk, pending_writes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloaded_chunks_receive_the_features_of_loaded_neighbours() {
+        let registry = VoxelRegistry::default();
+        let leaves = registry.by_name("leaves").unwrap();
+        let size = glam::UVec3::splat(8);
+        let mut chunks = HashMap::new();
+        let mut pending_writes = PendingWrites::default();
+
+        // A tree in the chunk at the origin reaches into the chunk above, which is not loaded yet
+        let mut placed_writes = PendingWrites::default();
+        placed_writes.push(size, glam::IVec3::ZERO, glam::ivec3(3, 8, 3), leaves);
+        let source = Chunk::new(glam::IVec3::ZERO, size);
+        insert_chunk(&mut chunks, &mut pending_writes, source, placed_writes);
+
+        let (written, _) = insert_chunk(
+            &mut chunks,
+            &mut pending_writes,
+            Chunk::new(glam::IVec3::Y, size),
+            PendingWrites::default(),
+        );
+        assert_eq!(written, [glam::ivec3(3, 8, 3)]);
+
+        // The chunk above is unloaded without being saved, and generated again
+        chunks.remove(&glam::IVec3::Y);
+        pending_writes.remove_source(glam::IVec3::Y);
+        insert_chunk(
+            &mut chunks,
+            &mut pending_writes,
+            Chunk::new(glam::IVec3::Y, size),
+            PendingWrites::default(),
+        );
+        assert_eq!(
+            chunks[&glam::IVec3::Y].get_voxel(glam::uvec3(3, 0, 3)),
+            Some(&leaves)
+        );
+
+        // The writes are dropped once the chunk that placed them is unloaded
+        chunks.remove(&glam::IVec3::ZERO);
+        pending_writes.remove_source(glam::IVec3::ZERO);
+        assert!(pending_writes.is_empty());
+    }
+}
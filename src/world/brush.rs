@@ -0,0 +1,323 @@
+use crate::chunk::{registry::VoxelRegistry, Voxel};
+
+use super::raycast::RaycastHit;
+
+/// Shape of the region edited by a [`Brush`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BrushShape {
+    /// Voxels whose center is within the radius of the brush.
+    #[default]
+    Sphere,
+    /// Voxels within the radius of the brush along every axis.
+    Cube,
+}
+
+/// Edit applied by a [`Brush`] to the voxels in its region.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BrushMode {
+    /// Fills air and liquids with the voxel of the brush, against the face that was hit.
+    #[default]
+    Add,
+    /// Replaces every voxel with air.
+    Remove,
+    /// Makes every voxel solid if most of the voxels around it are solid, and removes it otherwise.
+    Smooth,
+    /// Removes the voxels above the target height, and fills air and liquids with the voxel of the
+    /// brush at or below it.
+    Flatten,
+    /// Replaces the solid voxels on the surface with the voxel of the brush.
+    Paint,
+}
+
+/// Tool editing the voxels around the voxel targeted in the world.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Brush {
+    pub shape: BrushShape,
+    pub mode: BrushMode,
+    /// Radius of the region edited, in voxels. A radius of zero only edits a single voxel.
+    pub radius: u32,
+    /// Voxel placed by the brush.
+    pub voxel: Voxel,
+    /// Height of the surface left by [`BrushMode::Flatten`], in voxels.
+    pub target_height: i32,
+}
+
+impl Brush {
+    pub fn new(voxel: Voxel) -> Self {
+        Self {
+            shape: BrushShape::default(),
+            mode: BrushMode::default(),
+            radius: 3,
+            voxel,
+            target_height: 0,
+        }
+    }
+
+    /// Returns the voxels changed by a stroke of the brush on the given hit, along with their new
+    /// type.
+    ///
+    /// `get_voxel` looks up the voxel at a position in the world, and positions it returns `None`
+    /// for are left untouched. Every edit is computed from the voxels before the stroke, so the
+    /// edits can be applied in any order.
+    pub fn stroke(
+        &self,
+        hit: &RaycastHit,
+        registry: &VoxelRegistry,
+        get_voxel: impl Fn(glam::IVec3) -> Option<Voxel>,
+    ) -> Vec<(glam::IVec3, Voxel)> {
+        let center = match self.mode {
+            BrushMode::Add => hit.position + hit.normal(),
+            _ => hit.position,
+        };
+        let fillable = |voxel: Voxel| voxel.is_air() || registry.is_liquid(voxel);
+
+        let mut edits = Vec::new();
+        for position in self.region(center) {
+            let Some(current) = get_voxel(position) else {
+                continue;
+            };
+
+            let voxel = match self.mode {
+                BrushMode::Add => fillable(current).then_some(self.voxel),
+                BrushMode::Remove => Some(Voxel::AIR),
+                BrushMode::Smooth => smooth(position, current, registry, &get_voxel),
+                BrushMode::Flatten if position.y > self.target_height => Some(Voxel::AIR),
+                BrushMode::Flatten => fillable(current).then_some(self.voxel),
+                BrushMode::Paint => {
+                    let exposed = NEIGHBOURS.iter().any(|offset| {
+                        get_voxel(position + *offset).is_some_and(|voxel| !registry.is_solid(voxel))
+                    });
+                    (registry.is_solid(current) && exposed).then_some(self.voxel)
+                }
+            };
+
+            if let Some(voxel) = voxel.filter(|voxel| *voxel != current) {
+                edits.push((position, voxel));
+            }
+        }
+        edits
+    }
+
+    /// Returns the positions of the voxels in the region of the brush around `center`.
+    fn region(&self, center: glam::IVec3) -> impl Iterator<Item = glam::IVec3> + '_ {
+        let radius = self.radius as i32;
+        let max_distance_squared = (self.radius as f32 + 0.5).powi(2);
+
+        (-radius..=radius)
+            .flat_map(move |x| {
+                (-radius..=radius)
+                    .flat_map(move |y| (-radius..=radius).map(move |z| glam::ivec3(x, y, z)))
+            })
+            .filter(move |offset| match self.shape {
+                BrushShape::Sphere => offset.as_vec3().length_squared() <= max_distance_squared,
+                BrushShape::Cube => true,
+            })
+            .map(move |offset| center + offset)
+    }
+}
+
+/// Offsets of the voxels sharing a face with a voxel.
+const NEIGHBOURS: [glam::IVec3; 6] = [
+    glam::IVec3::X,
+    glam::IVec3::NEG_X,
+    glam::IVec3::Y,
+    glam::IVec3::NEG_Y,
+    glam::IVec3::Z,
+    glam::IVec3::NEG_Z,
+];
+
+/// Returns the voxel left at `position` by smoothing, from the majority of the loaded voxels in
+/// the 3x3x3 block around it.
+///
+/// Voxels that become solid take the most common solid voxel around them.
+fn smooth(
+    position: glam::IVec3,
+    current: Voxel,
+    registry: &VoxelRegistry,
+    get_voxel: impl Fn(glam::IVec3) -> Option<Voxel>,
+) -> Option<Voxel> {
+    let mut loaded = 0;
+    // Number of occurrences of every solid voxel, in the order they are first found
+    let mut solids: Vec<(Voxel, usize)> = Vec::new();
+    for x in -1..=1 {
+        for y in -1..=1 {
+            for z in -1..=1 {
+                let Some(voxel) = get_voxel(position + glam::ivec3(x, y, z)) else {
+                    continue;
+                };
+
+                loaded += 1;
+                if registry.is_solid(voxel) {
+                    match solids.iter_mut().find(|(solid, _)| *solid == voxel) {
+                        Some((_, count)) => *count += 1,
+                        None => solids.push((voxel, 1)),
+                    }
+                }
+            }
+        }
+    }
+
+    let solid_count: usize = solids.iter().map(|(_, count)| count).sum();
+    let should_be_solid = solid_count * 2 > loaded;
+    if should_be_solid == registry.is_solid(current) {
+        return None;
+    }
+
+    if !should_be_solid {
+        return Some(Voxel::AIR);
+    }
+
+    // Keep the first of the most common voxels, so that ties are broken the same way every time
+    let mut most_common = solids[0];
+    for solid in &solids[1..] {
+        if solid.1 > most_common.1 {
+            most_common = *solid;
+        }
+    }
+    Some(most_common.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::chunk::mesh::{Axis, Direction};
+
+    /// Extent of the test world along every axis, from `-EXTENT` to `EXTENT` inclusive.
+    const EXTENT: i32 = 8;
+
+    /// Creates a test world filled with `ground` at and below `y = 0`, and air above it.
+    fn world(ground: Voxel) -> HashMap<glam::IVec3, Voxel> {
+        let mut world = HashMap::new();
+        for x in -EXTENT..=EXTENT {
+            for y in -EXTENT..=EXTENT {
+                for z in -EXTENT..=EXTENT {
+                    let voxel = if y <= 0 { ground } else { Voxel::AIR };
+                    world.insert(glam::ivec3(x, y, z), voxel);
+                }
+            }
+        }
+        world
+    }
+
+    /// Returns a hit on the top face of the voxel at the given position.
+    fn hit_top(world: &HashMap<glam::IVec3, Voxel>, position: glam::IVec3) -> RaycastHit {
+        RaycastHit {
+            position,
+            axis: Axis::Y,
+            direction: Direction::Positive,
+            distance: 1.0,
+            voxel: world[&position],
+        }
+    }
+
+    fn stroke(brush: &Brush, world: &mut HashMap<glam::IVec3, Voxel>, registry: &VoxelRegistry) {
+        let hit = hit_top(world, glam::IVec3::ZERO);
+        let edits = brush.stroke(&hit, registry, |position| world.get(&position).copied());
+        world.extend(edits);
+    }
+
+    #[test]
+    fn add_and_remove_spheres_and_cubes() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let mut brush = Brush::new(stone);
+        brush.radius = 2;
+
+        // Adding fills a sphere centered against the face that was hit
+        let mut sphere = world(stone);
+        stroke(&brush, &mut sphere, &registry);
+        assert_eq!(sphere[&glam::ivec3(0, 3, 0)], stone);
+        assert_eq!(sphere[&glam::ivec3(2, 1, 0)], stone);
+        assert_eq!(sphere[&glam::ivec3(2, 3, 0)], Voxel::AIR);
+        assert_eq!(sphere[&glam::ivec3(0, 4, 0)], Voxel::AIR);
+
+        brush.shape = BrushShape::Cube;
+        let mut cube = world(stone);
+        stroke(&brush, &mut cube, &registry);
+        assert_eq!(cube[&glam::ivec3(2, 3, 2)], stone);
+        assert_eq!(cube[&glam::ivec3(0, 4, 0)], Voxel::AIR);
+
+        // Removing a cube leaves no voxel within the radius of the hit
+        brush.mode = BrushMode::Remove;
+        stroke(&brush, &mut cube, &registry);
+        assert_eq!(cube[&glam::ivec3(2, -2, 2)], Voxel::AIR);
+        assert_eq!(cube[&glam::ivec3(0, 2, 0)], Voxel::AIR);
+        assert_eq!(cube[&glam::ivec3(0, 3, 0)], stone);
+        assert_eq!(cube[&glam::ivec3(3, 0, 0)], stone);
+    }
+
+    #[test]
+    fn strokes_skip_unloaded_voxels() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let mut world = world(stone);
+        world.retain(|position, _| position.x <= 0);
+
+        let mut brush = Brush::new(stone);
+        brush.mode = BrushMode::Remove;
+        let hit = hit_top(&world, glam::IVec3::ZERO);
+        let edits = brush.stroke(&hit, &registry, |position| world.get(&position).copied());
+
+        assert!(!edits.is_empty());
+        assert!(edits.iter().all(|(position, _)| position.x <= 0));
+    }
+
+    #[test]
+    fn smoothing_removes_spikes_and_fills_holes() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let dirt = registry.by_name("dirt").unwrap();
+        let mut world = world(stone);
+        world.insert(glam::ivec3(0, 1, 0), dirt);
+        world.insert(glam::ivec3(2, 0, 0), Voxel::AIR);
+        world.insert(glam::ivec3(2, -1, 0), dirt);
+
+        let mut brush = Brush::new(dirt);
+        brush.mode = BrushMode::Smooth;
+        stroke(&brush, &mut world, &registry);
+
+        assert_eq!(world[&glam::ivec3(0, 1, 0)], Voxel::AIR);
+        // Holes are filled with the most common voxel around them, not the voxel of the brush
+        assert_eq!(world[&glam::ivec3(2, 0, 0)], stone);
+        assert_eq!(world[&glam::ivec3(0, 0, 0)], stone);
+    }
+
+    #[test]
+    fn flattening_levels_the_terrain_to_the_target_height() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let dirt = registry.by_name("dirt").unwrap();
+        let mut world = world(stone);
+        world.insert(glam::ivec3(1, 1, 0), stone);
+        world.insert(glam::ivec3(1, 2, 0), stone);
+
+        let mut brush = Brush::new(dirt);
+        brush.mode = BrushMode::Flatten;
+        brush.target_height = 1;
+        stroke(&brush, &mut world, &registry);
+
+        assert_eq!(world[&glam::ivec3(1, 2, 0)], Voxel::AIR);
+        assert_eq!(world[&glam::ivec3(1, 1, 0)], stone);
+        assert_eq!(world[&glam::ivec3(0, 1, 0)], dirt);
+        assert_eq!(world[&glam::ivec3(0, 0, 0)], stone);
+    }
+
+    #[test]
+    fn painting_only_replaces_the_surface() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let grass = registry.by_name("grass").unwrap();
+        let mut world = world(stone);
+
+        let mut brush = Brush::new(grass);
+        brush.mode = BrushMode::Paint;
+        stroke(&brush, &mut world, &registry);
+
+        assert_eq!(world[&glam::ivec3(0, 0, 0)], grass);
+        assert_eq!(world[&glam::ivec3(2, 0, 1)], grass);
+        assert_eq!(world[&glam::ivec3(0, -1, 0)], stone);
+        assert_eq!(world[&glam::ivec3(0, 1, 0)], Voxel::AIR);
+    }
+}
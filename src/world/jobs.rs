@@ -0,0 +1,103 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Queue of chunk jobs waiting to be dispatched to the worker pool.
+///
+/// Jobs are identified by the grid position of their chunk, and popped in order of priority, lowest
+/// first. A chunk can only be queued once, pushing it again while it is queued is a no-op.
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    heap: BinaryHeap<Reverse<(i32, [i32; 3])>>,
+    queued: HashSet<glam::IVec3>,
+}
+
+impl JobQueue {
+    /// Creates a new empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a job for the given chunk.
+    ///
+    /// Returns `false` if the chunk was already queued.
+    pub fn push(&mut self, position: glam::IVec3, priority: i32) -> bool {
+        if !self.queued.insert(position) {
+            return false;
+        }
+
+        self.heap.push(Reverse((priority, position.to_array())));
+        true
+    }
+
+    /// Returns the number of queued jobs.
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Removes the job with the lowest priority value from the queue.
+    pub fn pop(&mut self) -> Option<glam::IVec3> {
+        let Reverse((_, position)) = self.heap.pop()?;
+        let position = glam::IVec3::from_array(position);
+        self.queued.remove(&position);
+        Some(position)
+    }
+
+    /// Recomputes the priority of every queued job.
+    pub fn reprioritise(&mut self, priority: impl Fn(glam::IVec3) -> i32) {
+        self.heap = self
+            .heap
+            .drain()
+            .map(|Reverse((_, position))| {
+                Reverse((priority(glam::IVec3::from_array(position)), position))
+            })
+            .collect();
+    }
+
+    /// Removes every queued job for which `keep` returns `false`.
+    pub fn retain(&mut self, keep: impl Fn(glam::IVec3) -> bool) {
+        self.heap
+            .retain(|Reverse((_, position))| keep(glam::IVec3::from_array(*position)));
+        self.queued.retain(|position| keep(*position));
+    }
+
+    /// Removes every queued job.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.queued.clear();
+    }
+}
+
+/// Counter identifying the current generation of the world.
+///
+/// The epoch is advanced whenever the world is cleared, so that jobs dispatched for the previous
+/// world can be cancelled before they run, and their results discarded when they arrive.
+#[derive(Debug, Clone, Default)]
+pub struct Epoch(Arc<AtomicU64>);
+
+impl Epoch {
+    /// Returns the current epoch.
+    pub fn current(&self) -> u64 {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Advances to the next epoch, invalidating every job dispatched in the current one.
+    pub fn advance(&self) {
+        self.0.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Output of a chunk job, sent back to the main thread.
+pub struct JobResult<T> {
+    /// Epoch the job was dispatched in.
+    pub epoch: u64,
+    /// Grid position of the chunk the job ran for.
+    pub grid_position: glam::IVec3,
+    /// Output of the job, or `None` if the job was cancelled before it ran.
+    pub output: Option<T>,
+}
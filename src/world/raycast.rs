@@ -0,0 +1,283 @@
+use crate::chunk::{
+    mesh::{Axis, Direction},
+    Voxel,
+};
+
+/// Voxel hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    /// Position of the voxel in the world.
+    pub position: glam::IVec3,
+    /// Axis of the face the ray entered the voxel through.
+    pub axis: Axis,
+    /// Direction the face the ray entered the voxel through is facing.
+    pub direction: Direction,
+    /// Distance travelled by the ray before entering the voxel.
+    pub distance: f32,
+    /// Type of the voxel.
+    pub voxel: Voxel,
+}
+
+impl RaycastHit {
+    /// Returns the normal of the face the ray entered the voxel through.
+    pub fn normal(&self) -> glam::IVec3 {
+        self.axis.get_normal(self.direction).as_ivec3()
+    }
+}
+
+/// Casts a ray through the voxel grid, and returns the first voxel accepted by `filter`.
+///
+/// Voxels are visited in the order the ray passes through them, following Amanatides and Woo's
+/// "A Fast Voxel Traversal Algorithm for Ray Tracing". `get_voxel` looks up the voxel at a position
+/// in the world, and the ray stops without a hit at the first position it returns `None` for. Air
+/// is never hit.
+///
+/// If the ray starts inside an accepted voxel, that voxel is hit at a distance of zero, through
+/// the face pointing back along the main axis of the ray.
+pub fn raycast(
+    origin: glam::Vec3,
+    direction: glam::Vec3,
+    max_distance: f32,
+    get_voxel: impl Fn(glam::IVec3) -> Option<Voxel>,
+    filter: impl Fn(Voxel) -> bool,
+) -> Option<RaycastHit> {
+    const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    let direction = direction.normalize_or_zero();
+    if direction == glam::Vec3::ZERO {
+        return None;
+    }
+
+    let mut position = origin.floor().as_ivec3();
+    let step = glam::IVec3::from_array(direction.to_array().map(|component| {
+        if component > 0.0 {
+            1
+        } else if component < 0.0 {
+            -1
+        } else {
+            0
+        }
+    }));
+
+    // Distance along the ray between two voxel borders on each axis, and to the next border
+    let t_delta = direction.abs().recip();
+    let mut t_max = glam::Vec3::from_array(std::array::from_fn(|axis| match step[axis] {
+        1 => (position[axis] as f32 + 1.0 - origin[axis]) * t_delta[axis],
+        -1 => (origin[axis] - position[axis] as f32) * t_delta[axis],
+        _ => f32::INFINITY,
+    }));
+
+    let abs_direction = direction.abs();
+    let main_axis = (0..3)
+        .max_by(|a, b| abs_direction[*a].total_cmp(&abs_direction[*b]))
+        .expect("to have three axes");
+    let mut axis = AXES[main_axis];
+    let mut face_direction = if step[main_axis] > 0 {
+        Direction::Negative
+    } else {
+        Direction::Positive
+    };
+    let mut distance = 0.0;
+
+    loop {
+        let voxel = get_voxel(position)?;
+        if !voxel.is_air() && filter(voxel) {
+            return Some(RaycastHit {
+                position,
+                axis,
+                direction: face_direction,
+                distance,
+                voxel,
+            });
+        }
+
+        let next_axis = (0..3)
+            .min_by(|a, b| t_max[*a].total_cmp(&t_max[*b]))
+            .expect("to have three axes");
+        distance = t_max[next_axis];
+        if distance > max_distance {
+            return None;
+        }
+
+        position[next_axis] += step[next_axis];
+        t_max[next_axis] += t_delta[next_axis];
+        axis = AXES[next_axis];
+        face_direction = if step[next_axis] > 0 {
+            Direction::Negative
+        } else {
+            Direction::Positive
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        chunk::{registry::VoxelRegistry, Chunk},
+        utils::world_to_chunk,
+    };
+
+    const SIZE: glam::UVec3 = glam::UVec3::splat(8);
+
+    /// Creates the chunks around the origin, with the given voxels set.
+    fn chunks(voxels: &[(glam::IVec3, Voxel)]) -> HashMap<glam::IVec3, Chunk> {
+        let mut chunks = HashMap::new();
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let grid_position = glam::ivec3(x, y, z);
+                    chunks.insert(grid_position, Chunk::new(grid_position, SIZE));
+                }
+            }
+        }
+
+        for (position, voxel) in voxels {
+            let (grid_position, local_position) = world_to_chunk(*position, SIZE);
+            chunks
+                .get_mut(&grid_position)
+                .unwrap()
+                .set_voxel(local_position, *voxel);
+        }
+        chunks
+    }
+
+    fn cast(
+        chunks: &HashMap<glam::IVec3, Chunk>,
+        origin: glam::Vec3,
+        direction: glam::Vec3,
+        max_distance: f32,
+        filter: impl Fn(Voxel) -> bool,
+    ) -> Option<RaycastHit> {
+        raycast(
+            origin,
+            direction,
+            max_distance,
+            |position| {
+                let (grid_position, local_position) = world_to_chunk(position, SIZE);
+                chunks
+                    .get(&grid_position)?
+                    .get_voxel(local_position)
+                    .copied()
+            },
+            filter,
+        )
+    }
+
+    #[test]
+    fn ray_hits_the_face_it_enters_through() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let chunks = chunks(&[(glam::ivec3(5, 0, 0), stone)]);
+
+        let hit = cast(
+            &chunks,
+            glam::vec3(0.5, 0.5, 0.5),
+            glam::Vec3::X,
+            10.0,
+            |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(hit.position, glam::ivec3(5, 0, 0));
+        assert_eq!((hit.axis, hit.direction), (Axis::X, Direction::Negative));
+        assert_eq!(hit.normal(), glam::IVec3::NEG_X);
+        assert_eq!(hit.distance, 4.5);
+        assert_eq!(hit.voxel, stone);
+    }
+
+    #[test]
+    fn ray_crosses_into_negative_chunks() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let chunks = chunks(&[(glam::ivec3(2, -3, -1), stone)]);
+
+        let hit = cast(
+            &chunks,
+            glam::vec3(2.5, 3.5, -0.5),
+            glam::Vec3::NEG_Y,
+            10.0,
+            |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(hit.position, glam::ivec3(2, -3, -1));
+        assert_eq!((hit.axis, hit.direction), (Axis::Y, Direction::Positive));
+        assert_eq!(hit.distance, 5.5);
+    }
+
+    #[test]
+    fn diagonal_ray_visits_every_voxel_it_passes_through() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let wall = (0..SIZE.y as i32)
+            .map(|y| (glam::ivec3(3, y, 0), stone))
+            .collect::<Vec<_>>();
+        let chunks = chunks(&wall);
+
+        let direction = glam::vec3(1.0, 1.0, 0.0);
+        let hit = cast(&chunks, glam::vec3(0.5, 0.2, 0.5), direction, 10.0, |_| {
+            true
+        })
+        .unwrap();
+
+        // The ray reaches x = 3 after 2.5 voxels along both axes, at a height of 2.7
+        assert_eq!(hit.position, glam::ivec3(3, 2, 0));
+        assert_eq!((hit.axis, hit.direction), (Axis::X, Direction::Negative));
+        assert!((hit.distance - 2.5 * std::f32::consts::SQRT_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn filtered_voxels_are_passed_through() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let water = registry.by_name("water").unwrap();
+        let chunks = chunks(&[(glam::ivec3(0, 0, 2), water), (glam::ivec3(0, 0, 4), stone)]);
+        let origin = glam::vec3(0.5, 0.5, 0.5);
+
+        let hit = cast(&chunks, origin, glam::Vec3::Z, 10.0, |_| true).unwrap();
+        assert_eq!(hit.voxel, water);
+
+        let hit = cast(&chunks, origin, glam::Vec3::Z, 10.0, |voxel| {
+            !registry.is_liquid(voxel)
+        })
+        .unwrap();
+        assert_eq!(hit.voxel, stone);
+        assert_eq!(hit.distance, 3.5);
+    }
+
+    #[test]
+    fn ray_stops_at_its_maximum_distance_and_unloaded_chunks() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let chunks = chunks(&[(glam::ivec3(0, 0, 4), stone)]);
+        let origin = glam::vec3(0.5, 0.5, 0.5);
+
+        assert!(cast(&chunks, origin, glam::Vec3::Z, 3.0, |_| true).is_none());
+        assert!(cast(&chunks, origin, glam::Vec3::Z, 4.0, |_| true).is_some());
+        assert!(cast(&chunks, origin, glam::Vec3::NEG_Z, 100.0, |_| true).is_none());
+        assert!(cast(&chunks, origin, glam::Vec3::ZERO, 100.0, |_| true).is_none());
+    }
+
+    #[test]
+    fn ray_starting_inside_a_voxel_hits_it() {
+        let registry = VoxelRegistry::default();
+        let stone = registry.by_name("stone").unwrap();
+        let chunks = chunks(&[(glam::ivec3(0, 0, 0), stone)]);
+
+        let hit = cast(
+            &chunks,
+            glam::vec3(0.5, 0.5, 0.5),
+            glam::vec3(0.2, -1.0, 0.0),
+            10.0,
+            |_| true,
+        )
+        .unwrap();
+
+        assert_eq!(hit.position, glam::IVec3::ZERO);
+        assert_eq!(hit.distance, 0.0);
+        assert_eq!(hit.normal(), glam::IVec3::Y);
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use crate::{
+    chunk::{registry::VoxelRegistry, Chunk, Voxel},
+    generation::features::PendingWrites,
+};
+
+/// Number of chunks along each axis of a region.
+const REGION_SIZE: i32 = 32;
+/// Number of chunks in a region.
+const REGION_VOLUME: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+
+/// Magic bytes at the start of every region file.
+const MAGIC: &[u8; 4] = b"VXRG";
+/// Version of the region file format.
+const VERSION: u32 = 3;
+/// Size of an entry of the offset table, a `u32` offset followed by a `u32` length.
+const ENTRY_SIZE: u64 = 8;
+/// Size of the region file header, including the offset table.
+const HEADER_SIZE: u64 = 8 + REGION_VOLUME as u64 * ENTRY_SIZE;
+
+/// Directory of region files storing the chunks of a single world.
+///
+/// Every region file stores a cube of [`REGION_SIZE`] chunks along each axis. A file starts with a
+/// header and a table holding the offset and length of every chunk in the region, followed by the
+/// compressed chunk payloads. Voxels are stored by name, so saved chunks stay valid when voxel
+/// types are added to or reordered in the registry.
+#[derive(Debug)]
+pub struct RegionStore {
+    directory: PathBuf,
+    /// Serializes access to the region files, which are read from worker threads.
+    lock: Mutex<()>,
+}
+
+impl RegionStore {
+    /// Creates a store keeping its region files in the given directory.
+    ///
+    /// The directory is created when the first chunk is saved.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Loads the chunk at the given grid position, along with the voxels of the features it placed
+    /// into neighbouring chunks.
+    ///
+    /// Returns `None` if the chunk was never saved, or was saved with a different size.
+    pub fn load_chunk(
+        &self,
+        grid_position: glam::IVec3,
+        size: glam::UVec3,
+        registry: &VoxelRegistry,
+    ) -> io::Result<Option<(Chunk, PendingWrites)>> {
+        let _lock = self.lock.lock().expect("to lock region store");
+
+        let mut file = match File::open(self.region_path(grid_position)) {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        read_header(&mut file)?;
+
+        let (offset, length) = read_entry(&mut file, grid_position)?;
+        if offset == 0 {
+            return Ok(None);
+        }
+
+        let mut payload = vec![0; length as usize];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut payload)?;
+
+        let (chunk, placed_writes) = decode_chunk(grid_position, &payload, registry)?;
+        Ok((chunk.size() == size).then_some((chunk, placed_writes)))
+    }
+
+    /// Saves the given chunk, replacing any previously saved version of it.
+    ///
+    /// The writes of `placed_writes` that the chunk placed are saved along with it, so that the
+    /// chunk doesn't need to be generated again to place its features once it is loaded.
+    pub fn save_chunk(
+        &self,
+        chunk: &Chunk,
+        placed_writes: &PendingWrites,
+        registry: &VoxelRegistry,
+    ) -> io::Result<()> {
+        let payload = encode_chunk(chunk, placed_writes, registry)?;
+        let length =
+            u32::try_from(payload.len()).map_err(|_| invalid_data("chunk payload too large"))?;
+
+        let _lock = self.lock.lock().expect("to lock region store");
+
+        std::fs::create_dir_all(&self.directory)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.region_path(chunk.grid_position))?;
+
+        if file.metadata()?.len() == 0 {
+            file.write_all(MAGIC)?;
+            file.write_all(&VERSION.to_le_bytes())?;
+            file.set_len(HEADER_SIZE)?;
+        } else {
+            read_header(&mut file)?;
+        }
+
+        // Overwrite the previous payload if the new one fits, otherwise append it to the file
+        let (previous_offset, previous_length) = read_entry(&mut file, chunk.grid_position)?;
+        let offset = if previous_offset != 0 && length <= previous_length {
+            file.seek(SeekFrom::Start(previous_offset as u64))?
+        } else {
+            file.seek(SeekFrom::End(0))?
+        };
+        let offset = u32::try_from(offset).map_err(|_| invalid_data("region file too large"))?;
+        file.write_all(&payload)?;
+
+        // The table is only updated once the payload is written, so that an interrupted save
+        // leaves the previous version of the chunk intact
+        file.seek(SeekFrom::Start(entry_offset(chunk.grid_position)))?;
+        file.write_all(&offset.to_le_bytes())?;
+        file.write_all(&length.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    /// Returns the path of the region file containing the chunk at the given grid position.
+    fn region_path(&self, grid_position: glam::IVec3) -> PathBuf {
+        let region = grid_position.div_euclid(glam::IVec3::splat(REGION_SIZE));
+        self.directory
+            .join(format!("r.{}.{}.{}.region", region.x, region.y, region.z))
+    }
+}
+
+/// Returns the offset of the table entry for the chunk at the given grid position.
+fn entry_offset(grid_position: glam::IVec3) -> u64 {
+    let local = grid_position.rem_euclid(glam::IVec3::splat(REGION_SIZE));
+    let index = local.x + local.y * REGION_SIZE + local.z * REGION_SIZE * REGION_SIZE;
+    8 + index as u64 * ENTRY_SIZE
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Checks that the file starts with a valid region header.
+fn read_header(file: &mut File) -> io::Result<()> {
+    let mut header = [0; 8];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+
+    if &header[..4] != MAGIC {
+        return Err(invalid_data("not a region file"));
+    }
+    if header[4..] != VERSION.to_le_bytes() {
+        return Err(invalid_data("unsupported region file version"));
+    }
+
+    Ok(())
+}
+
+/// Reads the offset and length of the chunk at the given grid position from the offset table.
+fn read_entry(file: &mut File, grid_position: glam::IVec3) -> io::Result<(u32, u32)> {
+    let mut entry = [0; ENTRY_SIZE as usize];
+    file.seek(SeekFrom::Start(entry_offset(grid_position)))?;
+    file.read_exact(&mut entry)?;
+
+    Ok((
+        u32::from_le_bytes(entry[..4].try_into().expect("to have 4 bytes")),
+        u32::from_le_bytes(entry[4..].try_into().expect("to have 4 bytes")),
+    ))
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Iterates over every position in a chunk of the given size, in storage order.
+fn chunk_positions(size: glam::UVec3) -> impl Iterator<Item = glam::UVec3> {
+    (0..size.z).flat_map(move |z| {
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| glam::uvec3(x, y, z)))
+    })
+}
+
+/// Serializes and compresses a chunk.
+///
+/// The payload consists of the chunk size, a palette of voxel names, the palette index of every
+/// voxel, the neighbours whose features the chunk received, and the writes of the features the
+/// chunk placed into other chunks, grouped by the chunk they fall into.
+fn encode_chunk(
+    chunk: &Chunk,
+    placed_writes: &PendingWrites,
+    registry: &VoxelRegistry,
+) -> io::Result<Vec<u8>> {
+    let size = chunk.size();
+    let mut palette: Vec<Voxel> = Vec::new();
+    let mut palette_index = |voxel: Voxel| match palette.iter().position(|entry| *entry == voxel) {
+        Some(index) => index as u16,
+        None => {
+            palette.push(voxel);
+            (palette.len() - 1) as u16
+        }
+    };
+
+    let mut indices = Vec::with_capacity(size.x as usize * size.y as usize * size.z as usize);
+    for position in chunk_positions(size) {
+        let voxel = *chunk
+            .get_voxel(position)
+            .expect("position to be within chunk");
+        indices.push(palette_index(voxel));
+    }
+
+    let placed_writes = placed_writes
+        .placed_by(chunk.grid_position)
+        .map(|(grid_position, writes)| {
+            let writes = writes
+                .iter()
+                .map(|(position, voxel)| (*position, palette_index(*voxel)))
+                .collect::<Vec<_>>();
+            (grid_position, writes)
+        })
+        .collect::<Vec<_>>();
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    for component in size.to_array() {
+        encoder.write_all(&component.to_le_bytes())?;
+    }
+
+    encoder.write_all(&(palette.len() as u16).to_le_bytes())?;
+    for voxel in palette {
+        let name = registry.get(voxel).name.as_bytes();
+        encoder.write_all(&(name.len() as u16).to_le_bytes())?;
+        encoder.write_all(name)?;
+    }
+
+    for index in indices {
+        encoder.write_all(&index.to_le_bytes())?;
+    }
+
+    encoder.write_all(&chunk.received_features().to_le_bytes())?;
+
+    encoder.write_all(&(placed_writes.len() as u32).to_le_bytes())?;
+    for (grid_position, writes) in placed_writes {
+        for component in grid_position.to_array() {
+            encoder.write_all(&component.to_le_bytes())?;
+        }
+        encoder.write_all(&(writes.len() as u32).to_le_bytes())?;
+        for (position, index) in writes {
+            for component in position.to_array() {
+                encoder.write_all(&component.to_le_bytes())?;
+            }
+            encoder.write_all(&index.to_le_bytes())?;
+        }
+    }
+
+    encoder.finish()
+}
+
+/// Decompresses and deserializes a chunk, along with the writes of the features it placed.
+///
+/// Voxels whose name is not in the registry are loaded as air.
+fn decode_chunk(
+    grid_position: glam::IVec3,
+    payload: &[u8],
+    registry: &VoxelRegistry,
+) -> io::Result<(Chunk, PendingWrites)> {
+    let mut decoder = ZlibDecoder::new(payload);
+
+    let size = glam::uvec3(
+        read_u32(&mut decoder)?,
+        read_u32(&mut decoder)?,
+        read_u32(&mut decoder)?,
+    );
+
+    let palette_len = read_u16(&mut decoder)?;
+    let mut palette = Vec::with_capacity(palette_len as usize);
+    for _ in 0..palette_len {
+        let mut name = vec![0; read_u16(&mut decoder)? as usize];
+        decoder.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|_| invalid_data("invalid voxel name"))?;
+        palette.push(registry.by_name(&name).unwrap_or(Voxel::AIR));
+    }
+
+    let read_voxel = |decoder: &mut ZlibDecoder<&[u8]>| {
+        palette
+            .get(read_u16(decoder)? as usize)
+            .copied()
+            .ok_or_else(|| invalid_data("invalid palette index"))
+    };
+
+    let mut chunk = Chunk::new(grid_position, size);
+    for position in chunk_positions(size) {
+        chunk.set_voxel(position, read_voxel(&mut decoder)?);
+    }
+
+    chunk.set_received_features(read_u32(&mut decoder)?);
+
+    let mut placed_writes = PendingWrites::default();
+    for _ in 0..read_u32(&mut decoder)? {
+        let target = glam::ivec3(
+            read_u32(&mut decoder)? as i32,
+            read_u32(&mut decoder)? as i32,
+            read_u32(&mut decoder)? as i32,
+        );
+        for _ in 0..read_u32(&mut decoder)? {
+            let position = glam::uvec3(
+                read_u32(&mut decoder)?,
+                read_u32(&mut decoder)?,
+                read_u32(&mut decoder)?,
+            );
+            let voxel = read_voxel(&mut decoder)?;
+            placed_writes.push(
+                size,
+                grid_position,
+                target * size.as_ivec3() + position.as_ivec3(),
+                voxel,
+            );
+        }
+    }
+
+    Ok((chunk, placed_writes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_store(name: &str) -> RegionStore {
+        let directory =
+            std::env::temp_dir().join(format!("voxel-region-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        RegionStore::new(directory)
+    }
+
+    #[test]
+    fn saved_chunks_are_loaded_unchanged() {
+        let registry = VoxelRegistry::default();
+        let store = temporary_store("round-trip");
+        let stone = registry.by_name("stone").expect("stone to be defined");
+        let water = registry.by_name("water").expect("water to be defined");
+
+        let size = glam::uvec3(4, 4, 4);
+        let mut chunk = Chunk::new(glam::ivec3(-1, 2, 33), size);
+        chunk.set_voxel(glam::uvec3(0, 0, 0), stone);
+        chunk.set_voxel(glam::uvec3(3, 1, 2), water);
+        chunk.set_received_features(0b1010_0001);
+
+        // Only the writes the chunk placed itself are saved along with it
+        let leaves = registry.by_name("leaves").expect("leaves to be defined");
+        let below = chunk.grid_position * size.as_ivec3() - glam::IVec3::Y;
+        let mut pending_writes = PendingWrites::default();
+        pending_writes.push(size, chunk.grid_position, below, leaves);
+        pending_writes.push(size, glam::IVec3::ZERO, below, stone);
+        store
+            .save_chunk(&chunk, &pending_writes, &registry)
+            .expect("to save chunk");
+
+        let (loaded, placed_writes) = store
+            .load_chunk(chunk.grid_position, size, &registry)
+            .expect("to load chunk")
+            .expect("chunk to be saved");
+        for position in chunk_positions(size) {
+            assert_eq!(loaded.get_voxel(position), chunk.get_voxel(position));
+        }
+        assert_eq!(loaded.received_features(), chunk.received_features());
+        assert!(placed_writes.placed_by(glam::IVec3::ZERO).next().is_none());
+        assert_eq!(
+            placed_writes
+                .placed_by(chunk.grid_position)
+                .collect::<Vec<_>>(),
+            [(
+                chunk.grid_position - glam::IVec3::Y,
+                [(glam::uvec3(0, 3, 0), leaves)].as_slice()
+            )]
+        );
+
+        let _ = std::fs::remove_dir_all(&store.directory);
+    }
+
+    #[test]
+    fn resaving_a_chunk_replaces_it() {
+        let registry = VoxelRegistry::default();
+        let store = temporary_store("resave");
+        let stone = registry.by_name("stone").expect("stone to be defined");
+        let size = glam::uvec3(4, 4, 4);
+
+        let mut chunk = Chunk::new(glam::ivec3(3, 0, 0), size);
+        store
+            .save_chunk(&chunk, &PendingWrites::default(), &registry)
+            .expect("to save chunk");
+        for position in chunk_positions(size) {
+            chunk.set_voxel(position, stone);
+        }
+        chunk.set_voxel(glam::uvec3(1, 1, 1), Voxel::AIR);
+        store
+            .save_chunk(&chunk, &PendingWrites::default(), &registry)
+            .expect("to save chunk");
+
+        let (loaded, _) = store
+            .load_chunk(chunk.grid_position, size, &registry)
+            .expect("to load chunk")
+            .expect("chunk to be saved");
+        assert_eq!(loaded.get_voxel(glam::uvec3(0, 0, 0)), Some(&stone));
+        assert_eq!(loaded.get_voxel(glam::uvec3(1, 1, 1)), Some(&Voxel::AIR));
+
+        assert!(store
+            .load_chunk(glam::ivec3(4, 0, 0), size, &registry)
+            .expect("to read region")
+            .is_none());
+        assert!(store
+            .load_chunk(chunk.grid_position, glam::uvec3(8, 8, 8), &registry)
+            .expect("to read region")
+            .is_none());
+
+        let _ = std::fs::remove_dir_all(&store.directory);
+    }
+}